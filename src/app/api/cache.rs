@@ -0,0 +1,240 @@
+use chrono::{DateTime, Local};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{Api, Capabilities};
+use crate::app::{
+    errors::AppError,
+    types::{AppResult, CoinList, Coins},
+};
+
+/// Settings of cache stored in config file, e.g.
+///
+/// ```toml
+/// [cache]
+/// ttl = 300
+/// dir = "/tmp/wtch-crpts"
+/// ```
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct CacheSettings {
+    /// Seconds a cached response is fresh. Responses are cached only if it's set.
+    pub ttl: Option<u64>,
+    pub dir: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct CacheEntry {
+    fetched_at: DateTime<Local>,
+    coins: CoinList,
+}
+
+/// Api wrapper, which stores responses on disk and serves them as long as they are fresh.
+/// Outdated responses are served (marked as stale), if the wrapped api fails.
+pub struct CachedApi {
+    api: Box<dyn Api>,
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl CachedApi {
+    pub fn new(api: Box<dyn Api>, dir: PathBuf, ttl: Duration) -> Self {
+        CachedApi { api, dir, ttl }
+    }
+
+    /// Directory to store responses if no other directory is given, e.g. `~/.cache/wtch-crpts`
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("wtch-crpts"))
+    }
+
+    /// Path of a cached response, e.g. `coinmarketcap-EUR_USD-BTC_ETH.json`
+    fn path(&self, symbols: &[&str], fiats: &[&str]) -> PathBuf {
+        let key = [self.api.get_name().to_string(), fiats.join(","), symbols.join(",")];
+        let name: String = key
+            .join("-")
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    fn read(&self, path: &PathBuf) -> Option<CacheEntry> {
+        let content = fs::read_to_string(path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("failed to parse cached response {} {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    fn write(&self, path: &PathBuf, entry: &CacheEntry) -> AppResult<()> {
+        let cache_error = |cause| AppError::Cache {
+            path: path.display().to_string(),
+            cause,
+        };
+        fs::create_dir_all(&self.dir).map_err(cache_error)?;
+        fs::write(path, serde_json::to_string(entry)?).map_err(cache_error)
+    }
+
+    fn is_fresh(&self, entry: &CacheEntry, now: &DateTime<Local>) -> bool {
+        now.signed_duration_since(entry.fetched_at)
+            .to_std()
+            .map(|age| age < self.ttl)
+            .unwrap_or(false)
+    }
+}
+
+fn cached_coins(entry: CacheEntry, stale: bool) -> Coins {
+    let mut coins = Coins::new(entry.coins);
+    coins.cached_at = Some(entry.fetched_at);
+    coins.stale = stale;
+    coins
+}
+
+impl Api for CachedApi {
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let path = self.path(symbols, fiats);
+        let entry = self.read(&path);
+        let now = Local::now();
+        match entry {
+            Some(entry) if self.is_fresh(&entry, &now) => {
+                debug!("cache hit {} fetched at {}", path.display(), entry.fetched_at);
+                return Ok(cached_coins(entry, false));
+            }
+            Some(_) => debug!("cache outdated {}", path.display()),
+            None => debug!("cache miss {}", path.display()),
+        }
+        match self.api.get_coin_details(symbols, fiats) {
+            Ok(coins) => {
+                let fresh = CacheEntry {
+                    fetched_at: now,
+                    coins: coins.list.clone(),
+                };
+                if let Err(e) = self.write(&path, &fresh) {
+                    warn!("failed to cache response {}", e);
+                }
+                Ok(coins)
+            }
+            Err(e) => match entry {
+                Some(entry) => {
+                    warn!("serve stale response of {}, because request failed {}", entry.fetched_at, e);
+                    Ok(cached_coins(entry, true))
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    fn get_endpoint(&self) -> &str {
+        self.api.get_endpoint()
+    }
+
+    fn get_name(&self) -> &str {
+        self.api.get_name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.api.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::decimal::Decimal;
+    use crate::app::types::CoinBuilder;
+    use std::cell::Cell;
+
+    /// Api returning a single coin with the number of requests as price, or an error if `fail` is set
+    struct CountingApi {
+        requests: Cell<u32>,
+        fail: Cell<bool>,
+    }
+
+    impl Api for CountingApi {
+        fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+            if self.fail.get() {
+                return Err(AppError::ApiParseMap { key: symbols[0].into() });
+            }
+            self.requests.set(self.requests.get() + 1);
+            let coin = CoinBuilder::default()
+                .symbol(symbols[0])
+                .price(fiats[0], i64::from(self.requests.get()))
+                .build()
+                .unwrap();
+            Ok(Coins::new(vec![coin]))
+        }
+
+        fn get_endpoint(&self) -> &str {
+            "http://localhost"
+        }
+
+        fn get_name(&self) -> &str {
+            "counting"
+        }
+    }
+
+    fn cache_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wtch-crpts-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn price(coins: &Coins) -> Option<f64> {
+        coins.list[0].quotes.values().next().and_then(|q| q.price.as_ref()).map(Decimal::to_f64)
+    }
+
+    #[test]
+    fn serve_fresh_response() {
+        let dir = cache_dir("fresh");
+        let api = CachedApi::new(
+            Box::new(CountingApi {
+                requests: Cell::new(0),
+                fail: Cell::new(false),
+            }),
+            dir.clone(),
+            Duration::from_secs(60),
+        );
+        let first = api.get_coin_details(&["BTC"], &["EUR"]).unwrap();
+        assert_eq!(price(&first), Some(1.0));
+        assert_eq!(first.cached_at, None);
+        let second = api.get_coin_details(&["BTC"], &["EUR"]).unwrap();
+        assert_eq!(price(&second), Some(1.0));
+        assert!(second.cached_at.is_some());
+        assert!(!second.stale);
+        // other symbols or fiats are cached separately
+        assert_eq!(price(&api.get_coin_details(&["BTC"], &["USD"]).unwrap()), Some(2.0));
+        assert!(dir.join("counting-EUR-BTC.json").exists());
+        assert_eq!(price(&api.get_coin_details(&["BTC", "ETH"], &["EUR", "USD"]).unwrap()), Some(3.0));
+        assert!(dir.join("counting-EUR_USD-BTC_ETH.json").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn serve_stale_response() {
+        let dir = cache_dir("stale");
+        let inner = CountingApi {
+            requests: Cell::new(0),
+            fail: Cell::new(false),
+        };
+        let api = CachedApi::new(Box::new(inner), dir.clone(), Duration::from_secs(0));
+        assert_eq!(price(&api.get_coin_details(&["BTC"], &["EUR"]).unwrap()), Some(1.0));
+        // outdated responses are fetched again
+        assert_eq!(price(&api.get_coin_details(&["BTC"], &["EUR"]).unwrap()), Some(2.0));
+
+        let failing = CountingApi {
+            requests: Cell::new(0),
+            fail: Cell::new(true),
+        };
+        let api = CachedApi::new(Box::new(failing), dir.clone(), Duration::from_secs(0));
+        let stale = api.get_coin_details(&["BTC"], &["EUR"]).unwrap();
+        assert_eq!(price(&stale), Some(2.0));
+        assert!(stale.stale);
+        assert!(api.get_coin_details(&["ETH"], &["EUR"]).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,9 +1,12 @@
 
+use chrono::{DateTime, Local};
 use reqwest::Url;
 use std::collections::HashMap;
 
-use super::Api;
+use super::{http::HttpClient, registry::Provider, Api, Capabilities, RawApi, RawResponse};
 use crate::app::{
+    config::ConfigFile,
+    decimal::Decimal,
     env::{get_env},
     errors::AppError,
     types::{AppResult, Coins},
@@ -11,15 +14,36 @@ use crate::app::{
 };
 
 
+const NAME: &str = "coinmarketcap";
 const HEADER_COINMARKETCAP_KEY: &str = "X-CMC_PRO_API_KEY";
+const ENDPOINT: &str = "https://pro-api.coinmarketcap.com/v1/cryptocurrency";
 const ENV_COINMARKETCAP_KEY: &str = "COINMARKETCAP_KEY";
 
+pub const PROVIDER: Provider = Provider {
+    name: NAME,
+    description: "CoinMarketCap, quotes in all fiats",
+    env_keys: &[ENV_COINMARKETCAP_KEY],
+    constructor: create,
+};
+
+fn create(endpoint: Option<&str>, config_file: &ConfigFile) -> AppResult<Box<dyn RawApi>> {
+    let client = HttpClient::new(&config_file.http)?;
+    Ok(Box::new(CoinMarketCap::new(endpoint).with_client(client)))
+}
+
 type CoinDetailMap = HashMap<String, Coin>;
 
 #[derive(Serialize, Debug, Deserialize, PartialEq)]
 struct QuoteData {
     #[serde(rename = "data")]
     pub details: CoinDetailMap,
+    #[serde(default)]
+    pub status: Option<Status>,
+}
+
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq)]
+struct Status {
+    pub credit_count: u32,
 }
 
 type QuoteMap = HashMap<String, Quote>;
@@ -29,70 +53,107 @@ pub struct Coin {
     pub id: i32,
     pub name: String,
     pub symbol: String,
+    #[serde(default)]
+    pub circulating_supply: Option<Decimal>,
     #[serde(rename = "quote")]
     quotes: QuoteMap,
 }
 
 #[derive(Serialize, Debug, Deserialize, Clone, PartialEq)]
 struct Quote {
-    pub price: f32,
-    pub volume_24h: f32,
-    pub percent_change_24h: f32,
-    pub market_cap: f64,
+    pub price: Decimal,
+    pub volume_24h: Decimal,
+    pub percent_change_24h: Decimal,
+    pub market_cap: Decimal,
+    #[serde(default)]
+    pub last_updated: Option<DateTime<Local>>,
 }
 
 pub struct CoinMarketCap {
-    client: reqwest::Client,
-    is_development: bool,
+    client: HttpClient,
+    endpoint: String,
 }
 
 impl CoinMarketCap {
-    pub fn new(is_development: bool) -> Self {
+    /// Api provider using its public endpoint, or any other endpoint (e.g. of `mock-server`)
+    pub fn new(endpoint: Option<&str>) -> Self {
         CoinMarketCap {
-            client: reqwest::Client::new(),
-            is_development
+            client: HttpClient::default(),
+            endpoint: endpoint.unwrap_or(ENDPOINT).into(),
         }
     }
 
-    fn to_coin(&self, api_coin: &Coin, fiat: &str) -> types::Coin {
+    /// Uses a client of other timeouts and retries, e.g. of config file
+    pub fn with_client(self, client: HttpClient) -> Self {
+        CoinMarketCap { client, ..self }
+    }
+
+    fn to_coin(&self, api_coin: &Coin, fiats: &[&str]) -> types::Coin {
+        let quotes = fiats
+            .iter()
+            .filter_map(|fiat| api_coin.quotes.get(*fiat).map(|q| (fiat.to_string(), q)))
+            .map(|(fiat, q)| {
+                // open, high and low aren't part of quotes of coinmarketcap
+                let quote = types::Quote {
+                    price: Some(q.price.clone()),
+                    percent_change_24h: Some(q.percent_change_24h.clone()),
+                    market_cap: Some(q.market_cap.clone()),
+                    volume_24h: Some(q.volume_24h.clone()),
+                    last_updated: q.last_updated,
+                    ..types::Quote::default()
+                };
+                (fiat, quote)
+            })
+            .collect();
         types::Coin {
             symbol: api_coin.symbol.to_owned(),
-            quote: api_coin.quotes.get(fiat).map(|q| q.price),
-            percent_change_24h: api_coin.quotes.get(fiat).map(|q| q.percent_change_24h),
-            market_cap: api_coin.quotes.get(fiat).map(|q| q.market_cap),
+            quotes,
+            supply: api_coin.circulating_supply.clone(),
         }
     }
 }
 
 impl Api for CoinMarketCap {
 
-    fn get_endpoint(&self) -> &str {
-        if self.is_development { 
-            "http://localhost:3000"
-        } else {
-            "https://pro-api.coinmarketcap.com/v1/cryptocurrency"
+    fn get_name(&self) -> &str {
+        NAME
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            credits: true,
+            key_env: Some(ENV_COINMARKETCAP_KEY),
+            ..Capabilities::default()
         }
     }
 
-    fn get_coin_details(&self, symbols: &[&str], fiat: &str) -> AppResult<Coins> {
-            let params = [("symbol", symbols.join(",")), ("convert", fiat.into())];
-            let endpoint = if self.is_development {
-                    format!("{}/quotes", self.get_endpoint())
-                } else {
-                    format!("{}/quotes/latest", self.get_endpoint())
-                };
-            let url = Url::parse_with_params(&endpoint, &params).map_err(AppError::ApiParseUrl)?;
-            let key: String = get_env(ENV_COINMARKETCAP_KEY)?;
+    fn get_endpoint(&self) -> &str {
+        &self.endpoint
+    }
 
-            info!("fetch detail url {}", url);
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let response = self.request(symbols, fiats)?;
+        self.parse(&response.body, symbols, fiats)
+    }
+}
+
+impl RawApi for CoinMarketCap {
 
-            let data: QuoteData = self.client
-                    .get(url)
-                    .header(HEADER_COINMARKETCAP_KEY, key.to_owned())
-                    .send()
-                    .map_err(AppError::ApiRequest)?
-                    .json()
-                    .map_err(AppError::ApiRequest)?;
+    fn request(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<RawResponse> {
+        // all fiats are converted by a single request
+        let params = [("symbol", symbols.join(",")), ("convert", fiats.join(","))];
+        let endpoint = format!("{}/quotes/latest", self.get_endpoint().trim_end_matches('/'));
+        let url = Url::parse_with_params(&endpoint, &params).map_err(AppError::ApiParseUrl)?;
+        let key: String = get_env(ENV_COINMARKETCAP_KEY)?;
+
+        info!("fetch detail url {}", url);
+
+        let body = self.client.get(url.as_str(), &[(HEADER_COINMARKETCAP_KEY, key)])?;
+        Ok(RawResponse { url: url.into_string(), body })
+    }
+
+    fn parse(&self, body: &str, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+            let data: QuoteData = serde_json::from_str(body)?;
 
             let mut coin_list = Vec::new();
 
@@ -105,11 +166,17 @@ impl Api for CoinMarketCap {
                 // TODO: Show not found symbols in UI
                 // but for now just ingore those
                 if let Ok(cmc_coin) = result {
-                    coin_list.push(self.to_coin(cmc_coin, &fiat));
+                    coin_list.push(self.to_coin(cmc_coin, fiats));
                 };
             };
             info!("details {:?}", coin_list);
-            Ok(Coins::new(coin_list))
+            let mut coins = Coins::new(coin_list);
+            coins.credit_count = data.status.map(|s| s.credit_count);
+            Ok(coins)
+    }
+
+    fn secrets(&self) -> Vec<String> {
+        get_env(ENV_COINMARKETCAP_KEY).into_iter().collect()
     }
 }
 
@@ -119,12 +186,19 @@ mod tests {
 
     use super::*;
     use serde_json::json;
-    use crate::app::types::{CoinBuilder};
+    use std::path::Path;
+    use crate::app::api::fixture::read_fixture;
+    use crate::app::types::{CoinBuilder, QuoteBuilder};
     use crate::app::types;
 
     #[test]
     fn deserialize_cmc_coins() {
         let json = json!({
+        "status": {
+            "timestamp": "2019-01-01T15:40:47.237Z",
+            "error_code": 0,
+            "credit_count": 1
+        },
         "data": {
             "BTC": {
                 "id": 1,
@@ -142,10 +216,11 @@ mod tests {
         }});
         let result: QuoteData = serde_json::from_value(json).unwrap();
         let quote: Quote = Quote {
-            price: 1.0,
-            volume_24h: 2.0,
-            percent_change_24h: 50.0,
-            market_cap: 200.0,
+            price: Decimal::from(1),
+            volume_24h: Decimal::from(2),
+            percent_change_24h: Decimal::from(50),
+            market_cap: Decimal::from(200),
+            last_updated: None,
         };
         let mut quotes: QuoteMap = HashMap::new();
         quotes.insert("EUR".into(), quote.clone());
@@ -153,11 +228,13 @@ mod tests {
             id: 1,
             name: "Bitcoin".into(),
             symbol: "BTC".into(),
+            circulating_supply: None,
             quotes,
         };
         let mut details: CoinDetailMap = HashMap::new();
         details.insert("BTC".into(), detail.clone());
-        let expected: QuoteData = QuoteData { details };
+        let status = Some(Status { credit_count: 1 });
+        let expected: QuoteData = QuoteData { details, status };
 
         assert_eq!(result, expected)
     }
@@ -165,30 +242,77 @@ mod tests {
     #[test]
     fn to_coin() {
         let quote: Quote = Quote {
-            price: 1.1,
-            volume_24h: 2.2,
-            percent_change_24h: 55.0,
-            market_cap: 222.0,
+            price: Decimal::from(1.1),
+            volume_24h: Decimal::from(2.2),
+            percent_change_24h: Decimal::from(55.0),
+            market_cap: Decimal::from(222.0),
+            last_updated: None,
         };
         let mut quotes: QuoteMap = HashMap::new();
         quotes.insert("EUR".into(), quote.clone());
+        quotes.insert("USD".into(), quote.clone());
         let api_coin: Coin = Coin {
             id: 1,
             name: "Bitcoin".into(),
             symbol: "BTC".into(),
+            circulating_supply: None,
             quotes,
         };
 
-        let cmc = CoinMarketCap::new(false); 
-        let result = cmc.to_coin(&api_coin, &"EUR"); 
+        let cmc = CoinMarketCap::new(None); 
+        let result = cmc.to_coin(&api_coin, &["EUR", "CHF"]); 
+        let quote = QuoteBuilder::default()
+                                .price(Decimal::from(1.1))
+                                .percent_change_24h(Decimal::from(55.0))
+                                .market_cap(Decimal::from(222.0))
+                                .volume_24h(Decimal::from(2.2))
+                                .build()
+                                .unwrap();
         let expected: types::Coin = CoinBuilder::default()
                                 .symbol("BTC")
-                                .quote(Some(1.1))
-                                .percent_change_24h(Some(55.0))
-                                .market_cap(Some(222.0))
+                                .quote("EUR", quote)
                                 .build()
                                 .unwrap();
 
         assert_eq!(result, expected);
     }
+    #[test]
+    fn parse_large_and_small_prices() {
+        let body = r#"{
+            "data": {
+                "BTC": {"id": 1, "name": "Bitcoin", "symbol": "BTC", "quote": {"IRR": {
+                    "price": 172345678901.123456789,
+                    "volume_24h": 0,
+                    "percent_change_24h": 0.00000001,
+                    "market_cap": 3012345678901234567890.5
+                }}},
+                "SHIB": {"id": 5994, "name": "Shiba Inu", "symbol": "SHIB", "quote": {"USD": {
+                    "price": 0.000008123456789012,
+                    "volume_24h": 0,
+                    "percent_change_24h": -1.5,
+                    "market_cap": 4787615478.3
+                }}}
+            }
+        }"#;
+        let cmc = CoinMarketCap::new(None);
+        let btc = cmc.parse(body, &["BTC"], &["IRR"]).unwrap().list[0].quote("IRR").cloned().unwrap();
+        assert_eq!(btc.price.unwrap().to_string(), "172345678901.123456789");
+        assert_eq!(btc.percent_change_24h.unwrap().to_string(), "0.00000001");
+        assert_eq!(btc.market_cap.unwrap().to_string(), "3012345678901234567890.5");
+        let shib = cmc.parse(body, &["SHIB"], &["USD"]).unwrap().list[0].price("USD").cloned().unwrap();
+        assert_eq!(shib.to_string(), "0.000008123456789012");
+    }
+
+    #[test]
+    fn parse_fixture_fields() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("mock");
+        let response = read_fixture(&dir, "coinmarketcap", &["EUR"]).unwrap();
+        let coins = CoinMarketCap::new(None).parse(&response.to_string(), &["BTC"], &["EUR"]).unwrap();
+        let btc = coins.list[0].quote("EUR").unwrap();
+        let decimal = |s: &str| Some(s.parse::<Decimal>().unwrap());
+        assert_eq!(btc.percent_change_24h, decimal("-1.7744"));
+        assert_eq!(btc.volume_24h, decimal("3641265482.768179"));
+        assert_eq!(btc.last_updated.map(|t| t.timestamp()), Some(1_546_357_200));
+        assert_eq!(coins.list[0].supply, decimal("17456787"));
+    }
 }
@@ -0,0 +1,333 @@
+use super::{Api, Capabilities};
+use crate::app::{
+    constants::FIAT_LIST,
+    decimal::Decimal,
+    errors::AppError,
+    types::{AppResult, Coin, Coins, Quote},
+};
+
+// Common base to derive cross rates of pairs, which aren't quoted directly
+pub const CROSS_RATE_BASE: &str = "USD";
+
+/// Api wrapper, which allows any supported asset (e.g. `BTC`) as quote currency.
+/// Pairs, which the wrapped api doesn't quote directly, are derived via `CROSS_RATE_BASE`,
+/// e.g. ETH in BTC as `ETH/USD ÷ BTC/USD`. Fiats are requested one by one, if the wrapped api
+/// can't quote multiple fiats by a single request.
+pub struct CrossRateApi {
+    api: Box<dyn Api>,
+}
+
+impl CrossRateApi {
+    pub fn new(api: Box<dyn Api>) -> Self {
+        CrossRateApi { api }
+    }
+
+    fn fetch(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        if fiats.len() < 2 || self.api.capabilities().multiple_fiats {
+            return self.api.get_coin_details(symbols, fiats);
+        }
+        let mut list: Vec<Coin> = Vec::new();
+        let mut credit_count = None;
+        for fiat in fiats {
+            let coins = self.api.get_coin_details(symbols, &[fiat])?;
+            credit_count = add_credits(credit_count, coins.credit_count);
+            for coin in coins.list {
+                match list.iter_mut().find(|c| c.symbol == coin.symbol) {
+                    Some(merged) => {
+                        merged.quotes.extend(coin.quotes);
+                        merged.supply = merged.supply.take().or(coin.supply);
+                    }
+                    None => list.push(coin),
+                }
+            }
+        }
+        list.sort_by_key(|c| symbols.iter().position(|s| *s == c.symbol));
+        let mut coins = Coins::new(list);
+        coins.credit_count = credit_count;
+        Ok(coins)
+    }
+}
+
+/// Credits of two requests, if any of them reports them
+fn add_credits(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    }
+}
+
+/// Quote of `coin` in `quote` derived from quotes of both in base currency
+fn cross_rate(coin: &Coin, quote_coin: Option<&Coin>, quote: &str) -> Quote {
+    let base = coin.quote(CROSS_RATE_BASE);
+    let quote_base = quote_coin.and_then(|c| c.quote(CROSS_RATE_BASE));
+    let quote_price = quote_base.and_then(|q| q.price.as_ref());
+    let quote_change = quote_base.and_then(|q| q.percent_change_24h.as_ref());
+    let (one, hundred) = (Decimal::from(1), Decimal::from(100));
+    // factor of a change, e.g. `1.1` of `10%`
+    let factor = |change: &Decimal| change.checked_div(&hundred).map(|c| &one + &c);
+    Quote {
+        price: base
+            .and_then(|b| b.price.as_ref())
+            .and_then(|p| quote_price.and_then(|q| p.checked_div(q))),
+        percent_change_24h: base
+            .and_then(|b| b.percent_change_24h.as_ref())
+            .and_then(&factor)
+            .and_then(|c| quote_change.and_then(&factor).and_then(|q| c.checked_div(&q)))
+            .map(|f| &(&f - &one) * &hundred),
+        market_cap: base
+            .and_then(|b| b.market_cap.as_ref())
+            .and_then(|m| quote_price.and_then(|q| m.checked_div(q))),
+        derived_via: Some(format!(
+            "{}/{base} ÷ {}/{base}",
+            coin.symbol,
+            quote,
+            base = CROSS_RATE_BASE
+        )),
+        last_updated: base.and_then(|b| b.last_updated),
+        ..Quote::default()
+    }
+}
+
+/// Whether an error only means, that a pair isn't quoted by the api provider. Other errors (e.g. of an api key
+/// or a connection) fail the derived pairs as well.
+fn is_not_quoted(error: &AppError) -> bool {
+    matches!(error, AppError::UnsupportedFiat { .. } | AppError::ApiParseMap { .. })
+}
+
+impl Api for CrossRateApi {
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        // fiat currencies are quoted directly by all api providers
+        let (fiat_list, quotes): (Vec<&str>, Vec<&str>) = fiats.iter().partition(|f| FIAT_LIST.contains(f));
+        if quotes.is_empty() {
+            return self.fetch(symbols, fiats);
+        }
+        // fiats are requested again together with base currency, if there are no direct quotes
+        let (direct, mut based_fiats) = if self.api.capabilities().crypto_quotes {
+            match self.fetch(symbols, fiats) {
+                Ok(coins) => (coins, vec![]),
+                Err(e) if is_not_quoted(&e) => {
+                    info!("pairs not quoted directly, derive them via {} {}", CROSS_RATE_BASE, e);
+                    (Coins::new(vec![]), fiat_list.clone())
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            (Coins::new(vec![]), fiat_list.clone())
+        };
+        let has_quote = |symbol: &str, quote: &str| {
+            direct.list.iter().any(|c| c.symbol == symbol && c.quote(quote).is_some())
+        };
+        let missing: Vec<&str> = symbols
+            .iter()
+            .filter(|s| fiats.iter().any(|f| !has_quote(s, f)))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return Ok(direct);
+        }
+
+        let mut request = missing.clone();
+        request.extend(quotes.iter().filter(|q| !missing.contains(q)));
+        if !based_fiats.contains(&CROSS_RATE_BASE) {
+            based_fiats.push(CROSS_RATE_BASE);
+        }
+        let based = self.fetch(&request, &based_fiats)?;
+        let find = |list: &[Coin], symbol: &str| list.iter().position(|c| c.symbol == symbol);
+
+        let mut list = direct.list;
+        for symbol in &missing {
+            let based_coin = match find(&based.list, symbol) {
+                Some(i) => &based.list[i],
+                None => continue,
+            };
+            let index = find(&list, symbol).unwrap_or_else(|| {
+                list.push(Coin {
+                    symbol: symbol.to_string(),
+                    ..Coin::default()
+                });
+                list.len() - 1
+            });
+            let coin = &mut list[index];
+            if coin.supply.is_none() {
+                coin.supply = based_coin.supply.clone();
+            }
+            for fiat in &fiat_list {
+                if let (None, Some(quote)) = (coin.quote(fiat), based_coin.quote(fiat)) {
+                    coin.quotes.insert(fiat.to_string(), quote.clone());
+                }
+            }
+            for quote in &quotes {
+                if coin.quote(quote).is_none() {
+                    let quote_coin = find(&based.list, quote).map(|i| &based.list[i]);
+                    coin.quotes.insert(quote.to_string(), cross_rate(based_coin, quote_coin, quote));
+                }
+            }
+        }
+        list.sort_by_key(|c| symbols.iter().position(|s| *s == c.symbol));
+
+        let mut coins = Coins::new(list);
+        coins.credit_count = add_credits(direct.credit_count, based.credit_count);
+        Ok(coins)
+    }
+
+    fn get_endpoint(&self) -> &str {
+        self.api.get_endpoint()
+    }
+
+    fn get_name(&self) -> &str {
+        self.api.get_name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.api.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::types::{CoinBuilder, QuoteBuilder};
+
+    /// Api quoting coins in USD and EUR, and BTC in ETH. LTC needs a missing api key, other fiats fail.
+    struct UsdApi(Capabilities);
+
+    fn usd_api() -> Box<UsdApi> {
+        Box::new(UsdApi(Capabilities {
+            crypto_quotes: true,
+            ..Capabilities::default()
+        }))
+    }
+
+    impl Api for UsdApi {
+        fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+            assert!(self.0.multiple_fiats || fiats.len() == 1);
+            let mut list: Vec<Coin> = Vec::new();
+            for fiat in fiats {
+                let quotes: &[(&str, f64, f64, f64)] = match *fiat {
+                    "USD" => &[("BTC", 4000.0, 10.0, 70e9), ("ETH", 100.0, -1.0, 10e9)],
+                    "EUR" => &[("BTC", 3500.0, 9.0, 60e9)],
+                    "ETH" => &[("BTC", 40.0, 11.0, 7e8)],
+                    "LTC" => return Err(AppError::ApiKey { provider: "usd".into(), name: "USD_KEY".into() }),
+                    _ => return Err(AppError::ApiParseMap { key: fiat.to_string() }),
+                };
+                for q in quotes.iter().filter(|q| symbols.contains(&q.0)) {
+                    let quote = QuoteBuilder::default()
+                        .price(Decimal::from(q.1))
+                        .percent_change_24h(Decimal::from(q.2))
+                        .market_cap(Decimal::from(q.3))
+                        .build()
+                        .unwrap();
+                    match list.iter_mut().find(|c| c.symbol == q.0) {
+                        Some(coin) => {
+                            coin.quotes.insert(fiat.to_string(), quote);
+                        }
+                        None => list.push(CoinBuilder::default().symbol(q.0).quote(*fiat, quote).build().unwrap()),
+                    }
+                }
+            }
+            let mut coins = Coins::new(list);
+            coins.credit_count = Some(1);
+            Ok(coins)
+        }
+
+        fn get_endpoint(&self) -> &str {
+            "http://localhost"
+        }
+
+        fn get_name(&self) -> &str {
+            "usd"
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn derive_cross_rates() {
+        let api = CrossRateApi::new(usd_api());
+        let coins = api.get_coin_details(&["ETH", "BTC"], &["BTC"]).unwrap();
+        let eth = coins.list[0].quote("BTC").unwrap();
+        assert_eq!(coins.list[0].symbol, "ETH");
+        assert_eq!(eth.price, Some(Decimal::from(0.025)));
+        assert_eq!(eth.market_cap, Some(Decimal::from(2.5e6)));
+        assert_eq!(eth.percent_change_24h, Some(Decimal::from(-10.0)));
+        assert_eq!(eth.derived_via.as_deref(), Some("ETH/USD ÷ BTC/USD"));
+        assert_eq!(coins.list[1].price("BTC"), Some(&Decimal::from(1.0)));
+        assert_eq!(coins.list[1].quote("USD"), None);
+        assert_eq!(coins.credit_count, Some(1));
+    }
+
+    #[test]
+    fn prefer_direct_quotes() {
+        let api = CrossRateApi::new(usd_api());
+        let coins = api.get_coin_details(&["BTC", "ETH"], &["ETH"]).unwrap();
+        assert_eq!(coins.list[0].price("ETH"), Some(&Decimal::from(40.0)));
+        assert_eq!(coins.list[0].quote("ETH").unwrap().derived_via, None);
+        assert_eq!(coins.list[1].price("ETH"), Some(&Decimal::from(1.0)));
+        assert!(coins.list[1].quote("ETH").unwrap().derived_via.is_some());
+        assert_eq!(coins.credit_count, Some(2));
+
+        let coins = api.get_coin_details(&["BTC"], &["USD"]).unwrap();
+        assert_eq!(coins.list[0].quote("USD").unwrap().derived_via, None);
+        assert!(api.get_coin_details(&["BTC"], &["CHF"]).is_err());
+    }
+
+    #[test]
+    fn derive_with_fiats() {
+        let api = CrossRateApi::new(usd_api());
+        // direct request fails, because ETH isn't quoted in BTC
+        let coins = api.get_coin_details(&["ETH"], &["EUR", "USD", "BTC"]).unwrap();
+        let eth = &coins.list[0];
+        assert_eq!(eth.price("USD"), Some(&Decimal::from(100.0)));
+        assert_eq!(eth.price("BTC"), Some(&Decimal::from(0.025)));
+        assert_eq!(eth.quote("EUR"), None);
+        let coins = api.get_coin_details(&["BTC"], &["EUR", "ETH"]).unwrap();
+        assert_eq!(coins.list[0].price("EUR"), Some(&Decimal::from(3500.0)));
+        assert_eq!(coins.list[0].price("ETH"), Some(&Decimal::from(40.0)));
+    }
+
+    #[test]
+    fn derive_without_crypto_quotes() {
+        let api = CrossRateApi::new(Box::new(UsdApi(Capabilities::default())));
+        let coins = api.get_coin_details(&["BTC"], &["EUR", "ETH"]).unwrap();
+        assert_eq!(coins.list[0].price("EUR"), Some(&Decimal::from(3500.0)));
+        assert_eq!(coins.list[0].price("ETH"), Some(&Decimal::from(40.0)));
+        assert!(coins.list[0].quote("ETH").unwrap().derived_via.is_some());
+        assert_eq!(coins.credit_count, Some(1));
+    }
+
+    #[test]
+    fn request_fiats_one_by_one() {
+        let api = CrossRateApi::new(Box::new(UsdApi(Capabilities {
+            multiple_fiats: false,
+            ..Capabilities::default()
+        })));
+        let coins = api.get_coin_details(&["ETH", "BTC"], &["EUR", "USD"]).unwrap();
+        assert_eq!(coins.get_symbols(), vec!["ETH", "BTC"]);
+        assert_eq!(coins.list[1].price("EUR"), Some(&Decimal::from(3500.0)));
+        assert_eq!(coins.list[1].price("USD"), Some(&Decimal::from(4000.0)));
+        assert_eq!(coins.credit_count, Some(2));
+    }
+
+    #[test]
+    fn propagate_other_errors() {
+        let api = CrossRateApi::new(usd_api());
+        let result = api.get_coin_details(&["BTC"], &["LTC"]);
+        assert_eq!(result.err().map(|e| e.kind()), Some("api_key"));
+    }
+
+    #[test]
+    fn cross_rate_of_small_prices() {
+        let coin = |symbol: &str, price: &str| {
+            let price: Decimal = price.parse().unwrap();
+            CoinBuilder::default().symbol(symbol).price(CROSS_RATE_BASE, price).build().unwrap()
+        };
+        let quote = cross_rate(&coin("SHIB", "0.000008123456789"), Some(&coin("BTC", "64000")), "BTC");
+        assert_eq!(quote.price.unwrap().to_string(), "0.000000000126929012328125");
+        let quote = cross_rate(&coin("BTC", "64000"), Some(&coin("SHIB", "0.00000001")), "SHIB");
+        assert_eq!(quote.price.unwrap().to_string(), "6400000000000");
+        assert_eq!(cross_rate(&coin("BTC", "64000"), Some(&coin("XYZ", "0")), "XYZ").price, None);
+    }
+}
@@ -1,58 +1,154 @@
 
+use chrono::{Local, TimeZone};
 use reqwest::Url;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use super::Api;
+use super::{http::HttpClient, registry::Provider, Api, Capabilities, RawApi, RawResponse};
 use crate::app::{
+    config::ConfigFile,
+    decimal::Decimal,
     env::get_env,
     errors::AppError,
     types::{AppResult, Coins},
     types
 };
 
+const NAME: &str = "cryptocompare";
+const ENDPOINT: &str = "https://min-api.cryptocompare.com/data";
 const ENV_CRYPTOCOMPARE_KEY: &str = "CRYPTOCOMPARE_KEY";
 
+// Fiats cryptocompare aggregates rates in
+const FIATS: [&str; 36] = [
+    "USD", "EUR", "GBP", "JPY", "KRW", "CNY", "RUB", "AUD", "CAD", "CHF", "PLN", "BRL", "INR", "ZAR", "SEK", "NOK",
+    "DKK", "HKD", "SGD", "TRY", "MXN", "UAH", "IDR", "ILS", "CZK", "HUF", "THB", "MYR", "PHP", "ARS", "CLP", "VND",
+    "SAR", "AED", "NGN", "TWD",
+];
+
+pub const PROVIDER: Provider = Provider {
+    name: NAME,
+    description: "CryptoCompare, quotes in cryptocurrencies and formatted values",
+    env_keys: &[ENV_CRYPTOCOMPARE_KEY],
+    constructor: create,
+};
+
+fn create(endpoint: Option<&str>, config_file: &ConfigFile) -> AppResult<Box<dyn RawApi>> {
+    let client = HttpClient::new(&config_file.http)?;
+    Ok(Box::new(CryptoCompare::new(endpoint).with_client(client)))
+}
+
 type CoinMap = HashMap<String, QuoteMap>; 
 
 #[derive(Serialize, Debug, Deserialize, PartialEq)]
 struct CoinMapData {
     #[serde(rename = "RAW")]
     pub coins: CoinMap,
+    /// Same coins with values formatted for the website of cryptocompare
+    #[serde(rename = "DISPLAY", default)]
+    pub display: DisplayMap,
+}
+
+type DisplayMap = HashMap<String, HashMap<String, DisplayCoin>>;
+
+/// Formatted values, e.g. `€ 3,200.98`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+struct DisplayCoin {
+    #[serde(rename = "PRICE")]
+    price: Option<String>,
+    /// Change in percent without sign, e.g. `-0.80`
+    #[serde(rename = "CHANGEPCT24HOUR")]
+    percent_change_24h: Option<String>,
+    #[serde(rename = "MKTCAP")]
+    market_cap: Option<String>,
+    #[serde(rename = "VOLUME24HOURTO")]
+    volume_24h: Option<String>,
+    #[serde(rename = "HIGH24HOUR")]
+    high_24h: Option<String>,
+    #[serde(rename = "LOW24HOUR")]
+    low_24h: Option<String>,
+    #[serde(rename = "SUPPLY")]
+    supply: Option<String>,
+}
+
+impl DisplayCoin {
+    fn to_formatted(&self) -> types::FormattedQuote {
+        types::FormattedQuote {
+            price: self.price.clone(),
+            percent_change_24h: self.percent_change_24h.as_ref().map(|c| format!("{}%", c)),
+            market_cap: self.market_cap.clone(),
+            volume_24h: self.volume_24h.clone(),
+            high_24h: self.high_24h.clone(),
+            low_24h: self.low_24h.clone(),
+            supply: self.supply.clone(),
+        }
+    }
 }
 
 type QuoteMap = HashMap<String, Coin>; 
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
 pub struct Coin {
     #[serde(rename = "FROMSYMBOL")]
     pub symbol: String,
     #[serde(rename = "PRICE")]
-    price: f32,
+    price: Option<Decimal>,
+    /// Change in fiat, e.g. `-25.72`
     #[serde(rename = "CHANGE24HOUR")]
-    percent_change_24h: f32,
+    change_24h: Option<Decimal>,
+    /// Change in percent, e.g. `-0.797`
+    #[serde(rename = "CHANGEPCT24HOUR")]
+    percent_change_24h: Option<Decimal>,
     #[serde(rename = "MKTCAP")]
-    market_cap: f64,
+    market_cap: Option<Decimal>,
+    /// Volume in fiat, `VOLUME24HOUR` is the same in coins
+    #[serde(rename = "VOLUME24HOURTO")]
+    volume_24h: Option<Decimal>,
+    #[serde(rename = "OPEN24HOUR")]
+    open_24h: Option<Decimal>,
+    #[serde(rename = "HIGH24HOUR")]
+    high_24h: Option<Decimal>,
+    #[serde(rename = "LOW24HOUR")]
+    low_24h: Option<Decimal>,
+    #[serde(rename = "SUPPLY")]
+    supply: Option<Decimal>,
+    /// Unix timestamp in seconds
+    #[serde(rename = "LASTUPDATE")]
+    last_update: Option<i64>,
 }
 
 pub struct CryptoCompare {
-    client: reqwest::Client,
-    is_development: bool,
+    client: HttpClient,
+    endpoint: String,
 }
 
 impl CryptoCompare {
-    pub fn new(is_development: bool) -> Self {
+    /// Api provider using its public endpoint, or any other endpoint (e.g. of `mock-server`)
+    pub fn new(endpoint: Option<&str>) -> Self {
         CryptoCompare {
-            client: reqwest::Client::new(),
-            is_development
+            client: HttpClient::default(),
+            endpoint: endpoint.unwrap_or(ENDPOINT).into(),
         }
     }
 
-    fn to_coin(&self, api_coin: &Coin, _: &str) -> types::Coin {
-        types::Coin {
-            symbol: api_coin.symbol.to_owned(),
-            quote: Some(api_coin.price),
-            percent_change_24h: Some(api_coin.percent_change_24h),
-            market_cap: Some(api_coin.market_cap),
+    /// Uses a client of other timeouts and retries, e.g. of config file
+    pub fn with_client(self, client: HttpClient) -> Self {
+        CryptoCompare { client, ..self }
+    }
+
+    fn to_quote(&self, api_coin: &Coin, display: Option<&DisplayCoin>) -> types::Quote {
+        types::Quote {
+            price: api_coin.price.clone(),
+            percent_change_24h: api_coin.percent_change_24h.clone(),
+            market_cap: api_coin.market_cap.clone(),
+            change_24h: api_coin.change_24h.clone(),
+            volume_24h: api_coin.volume_24h.clone(),
+            open_24h: api_coin.open_24h.clone(),
+            high_24h: api_coin.high_24h.clone(),
+            low_24h: api_coin.low_24h.clone(),
+            last_updated: api_coin.last_update.and_then(|t| Local.timestamp_opt(t, 0).single()),
+            derived_via: None,
+            formatted: display.map(DisplayCoin::to_formatted),
         }
     }
 }
@@ -61,31 +157,45 @@ impl Api for CryptoCompare {
 
     // type ApiCoin = Coin;
 
-    fn get_endpoint(&self) -> &str {
-        if self.is_development { 
-            "http://localhost:3000/"
-        } else {
-            "https://min-api.cryptocompare.com/data/"
+    fn get_name(&self) -> &str {
+        NAME
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            fiats: &FIATS,
+            crypto_quotes: true,
+            key_env: Some(ENV_CRYPTOCOMPARE_KEY),
+            ..Capabilities::default()
         }
     }
 
-    fn get_coin_details(&self, symbols: &[&str], fiat: &str) -> AppResult<Coins> {
+    fn get_endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let response = self.request(symbols, fiats)?;
+        self.parse(&response.body, symbols, fiats)
+    }
+}
+
+impl RawApi for CryptoCompare {
+
+    fn request(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<RawResponse> {
         let key: String = get_env(ENV_CRYPTOCOMPARE_KEY)?;
-        let params = [("fsyms", symbols.join(",")), ("tsyms", fiat.into()), ("api_key", key)];
-        let endpoint = if self.is_development {
-                format!("{}/quotes", self.get_endpoint())
-            } else {
-                format!("{}/pricemultifull", self.get_endpoint())
-            };
+        // all fiats are converted by a single request
+        let params = [("fsyms", symbols.join(",")), ("tsyms", fiats.join(",")), ("api_key", key)];
+        let endpoint = format!("{}/pricemultifull", self.get_endpoint().trim_end_matches('/'));
         let url = Url::parse_with_params(&endpoint, &params).map_err(AppError::ApiParseUrl)?;
         info!("fetch detail url {}", url);
-        
-        let data: CoinMapData = self.client
-                .get(url)
-                .send()
-                .map_err(AppError::ApiRequest)?
-                .json()
-                .map_err(AppError::ApiRequest)?;
+
+        let body = self.client.get(url.as_str(), &[])?;
+        Ok(RawResponse { url: url.into_string(), body })
+    }
+
+    fn parse(&self, body: &str, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let data: CoinMapData = serde_json::from_str(body)?;
 
         let mut coin_list = Vec::new();       
         for symbol in symbols {
@@ -97,23 +207,41 @@ impl Api for CryptoCompare {
             // TODO: Show not found symbols in UI
             // but for now just ingore those
             if let Ok(quotes) = result {
-                let coin = quotes.get(fiat)
-                                .ok_or(AppError::ApiParseMap { key: String::from(fiat) });
-                if let Ok(c) = coin {
-                    coin_list.push(self.to_coin(c, &fiat));
+                // supply is the same in all fiats
+                let supply = quotes.values().find_map(|c| c.supply.clone());
+                let display = |fiat: &str| data.display.get(s).and_then(|d| d.get(fiat));
+                let quotes: BTreeMap<String, types::Quote> = fiats
+                    .iter()
+                    .filter_map(|fiat| quotes.get(*fiat).map(|c| (fiat.to_string(), self.to_quote(c, display(fiat)))))
+                    .collect();
+                if !quotes.is_empty() {
+                    coin_list.push(types::Coin { symbol: s.into(), quotes, supply });
                 }
             };
         };
         info!("details {:?}", coin_list);
         Ok(Coins::new(coin_list))
     }
+
+    fn secrets(&self) -> Vec<String> {
+        get_env(ENV_CRYPTOCOMPARE_KEY).into_iter().collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::app::api::fixture::read_api_db;
     use serde_json::json;
+    use std::path::Path;
+
+    /// Coins of route `quotes` of `mock/cryptocompare-api.json` in EUR
+    fn parse_fixture(symbols: &[&str]) -> Coins {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("mock");
+        let response = read_api_db(&dir, "cryptocompare", "quotes").unwrap();
+        CryptoCompare::new(None).parse(&response.to_string(), symbols, &["EUR"]).unwrap()
+    }
 
     #[test]
     fn deserialize_coins() {
@@ -127,6 +255,7 @@ mod tests {
                         "TOSYMBOL": "EUR",
                         "PRICE": 3200.98,
                         "CHANGE24HOUR": -25.71,
+                        "CHANGEPCT24HOUR": -0.797,
                         "MKTCAP": 56_110_256_905.26,
                     }
                 }
@@ -136,9 +265,11 @@ mod tests {
         
         let coin: Coin = Coin {
             symbol: "BTC".into(),
-            price: 3200.98,
-            percent_change_24h: -25.71,
-            market_cap: 56_110_256_905.26,
+            price: Some(Decimal::from(3200.98)),
+            change_24h: Some(Decimal::from(-25.71)),
+            percent_change_24h: Some(Decimal::from(-0.797)),
+            market_cap: Some(Decimal::from(56_110_256_905.26)),
+            ..Coin::default()
         };
         let mut quotes: QuoteMap = HashMap::new();
         quotes.insert("EUR".into(), coin.clone());
@@ -146,8 +277,71 @@ mod tests {
         let mut coins: CoinMap = HashMap::new();
         coins.insert("BTC".into(), quotes.clone());
 
-        let expected: CoinMapData = CoinMapData { coins };
+        let expected: CoinMapData = CoinMapData {
+            coins,
+            display: HashMap::new(),
+        };
 
         assert_eq!(result, expected)
     }
+
+    #[test]
+    fn parse_large_and_small_prices() {
+        let body = r#"{"RAW": {
+            "BTC": {"VND": {"FROMSYMBOL": "BTC", "PRICE": 1534567890123.987654321, "CHANGE24HOUR": 12345678.9,
+                "MKTCAP": 29876543210987654321.12}},
+            "SHIB": {"USD": {"FROMSYMBOL": "SHIB", "PRICE": 0.00000000012345678, "CHANGE24HOUR": -0.0000000001,
+                "CHANGEPCT24HOUR": -0.00081, "MKTCAP": 72730000.5}}
+        }}"#;
+        let cc = CryptoCompare::new(None);
+        let coins = cc.parse(body, &["BTC", "SHIB"], &["VND", "USD"]).unwrap();
+        let btc = coins.list[0].quote("VND").unwrap();
+        assert_eq!(btc.price.as_ref().unwrap().to_string(), "1534567890123.987654321");
+        assert_eq!(btc.market_cap.as_ref().unwrap().to_string(), "29876543210987654321.12");
+        let shib = coins.list[1].quote("USD").unwrap();
+        assert_eq!(shib.price.as_ref().unwrap().to_string(), "0.00000000012345678");
+        assert_eq!(shib.change_24h.as_ref().unwrap().to_string(), "-0.0000000001");
+        assert_eq!(shib.percent_change_24h.as_ref().unwrap().to_string(), "-0.00081");
+    }
+
+    #[test]
+    fn parse_fixture_fields() {
+        let coins = parse_fixture(&["BTC", "LTC"]);
+        let btc = coins.list[0].quote("EUR").unwrap();
+        let decimal = |s: &str| Some(s.parse::<Decimal>().unwrap());
+        assert_eq!(btc.price, decimal("3200.98"));
+        assert_eq!(btc.percent_change_24h, decimal("-0.7970992035206187"));
+        assert_eq!(btc.change_24h, decimal("-25.7199999999998"));
+        assert_eq!(btc.market_cap, decimal("56110256905.26"));
+        assert_eq!(btc.volume_24h, decimal("21377849.51802289"));
+        assert_eq!(btc.open_24h, decimal("3226.7"));
+        assert_eq!(btc.high_24h, decimal("3236.48"));
+        assert_eq!(btc.low_24h, decimal("3171.44"));
+        assert_eq!(btc.last_updated.map(|t| t.timestamp()), Some(1_549_733_663));
+        assert_eq!(coins.list[0].supply, decimal("17529087"));
+        assert_eq!(coins.list[1].supply, decimal("60392599.7738777"));
+        assert_eq!(coins.list[1].percent_change_24h("EUR"), decimal("-0.39661554732945153").as_ref());
+    }
+
+    #[test]
+    fn parse_fixture_unknown_symbols() {
+        let coins = parse_fixture(&["LTC", "XYZ"]);
+        assert_eq!(coins.list.len(), 1);
+        assert_eq!(coins.list[0].symbol, "LTC");
+    }
+
+    #[test]
+    fn parse_fixture_display() {
+        let coins = parse_fixture(&["BTC"]);
+        let formatted = coins.list[0].quote("EUR").and_then(|q| q.formatted.clone()).unwrap();
+        assert_eq!(formatted.price.as_deref(), Some("€ 3,200.98"));
+        assert_eq!(formatted.percent_change_24h.as_deref(), Some("-0.80%"));
+        assert_eq!(formatted.market_cap.as_deref(), Some("€ 56.11 B"));
+        assert_eq!(formatted.volume_24h.as_deref(), Some("€ 21,377,849.5"));
+        assert_eq!(formatted.supply.as_deref(), Some("Ƀ 17,529,087.0"));
+        // responses without display block are still parsed
+        let body = r#"{"RAW": {"BTC": {"EUR": {"FROMSYMBOL": "BTC", "PRICE": 3200.98}}}}"#;
+        let coins = CryptoCompare::new(None).parse(body, &["BTC"], &["EUR"]).unwrap();
+        assert_eq!(coins.list[0].quote("EUR").unwrap().formatted, None);
+    }
 }
@@ -0,0 +1,253 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use super::{http::HttpClient, json_path::JsonPath, registry::Provider, Api, Capabilities, RawApi, RawResponse};
+use crate::app::{
+    config::ConfigFile,
+    decimal::Decimal,
+    env::get_env,
+    errors::AppError,
+    types::{AppResult, Coins},
+    types,
+};
+
+const NAME: &str = "custom";
+
+pub const PROVIDER: Provider = Provider {
+    name: NAME,
+    description: "any http api configured in section [custom] of config file",
+    env_keys: &[],
+    constructor: create,
+};
+
+fn create(endpoint: Option<&str>, config_file: &ConfigFile) -> AppResult<Box<dyn RawApi>> {
+    let settings = config_file.custom.clone().ok_or_else(|| AppError::CustomProvider {
+        reason: "section [custom] is missing".into(),
+    })?;
+    let client = HttpClient::new(&config_file.http)?;
+    Ok(Box::new(CustomApi::new(settings, endpoint)?.with_client(client)))
+}
+
+/// Settings of section `[custom]` of config file. Placeholders `{symbols}` (comma separated), `{symbol}` and
+/// `{fiat}` are replaced in url and paths, environment variables (e.g. `${MY_KEY}`) in url and headers.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomSettings {
+    /// Url template, e.g. `https://example.com/quotes?symbols={symbols}&convert={fiat}`
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Json path of price, e.g. `$.data.{symbol}.{fiat}.price`
+    pub price: String,
+    pub percent_change_24h: Option<String>,
+    pub market_cap: Option<String>,
+}
+
+pub struct CustomApi {
+    client: HttpClient,
+    settings: CustomSettings,
+}
+
+impl CustomApi {
+    /// Api provider of given settings, whose url template is replaced by an endpoint (e.g. of `mock-server`)
+    pub fn new(settings: CustomSettings, endpoint: Option<&str>) -> AppResult<Self> {
+        let settings = CustomSettings {
+            url: endpoint.map(String::from).unwrap_or(settings.url),
+            ..settings
+        };
+        if settings.url.is_empty() {
+            return Err(AppError::CustomProvider { reason: "url is empty".into() });
+        }
+        // validates all paths before any request is sent
+        let paths = [Some(&settings.price), settings.percent_change_24h.as_ref(), settings.market_cap.as_ref()];
+        for path in paths.iter().flatten() {
+            path_of(path, "BTC", "USD")?;
+        }
+        Ok(CustomApi {
+            client: HttpClient::default(),
+            settings,
+        })
+    }
+
+    /// Uses a client of other timeouts and retries, e.g. of config file
+    pub fn with_client(self, client: HttpClient) -> Self {
+        CustomApi { client, ..self }
+    }
+
+    fn select(&self, response: &Value, path: Option<&String>, symbol: &str, fiat: &str) -> Option<Decimal> {
+        let value = path_of(path?, symbol, fiat).ok()?.select(response)?;
+        match value {
+            Value::Number(n) => n.to_string().parse().ok(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+fn path_of(template: &str, symbol: &str, fiat: &str) -> AppResult<JsonPath> {
+    template.replace("{symbol}", symbol).replace("{fiat}", fiat).parse()
+}
+
+/// Names of environment variables used in a template, e.g. `MY_KEY` of `Bearer ${MY_KEY}`
+fn env_names(template: &str) -> Vec<&str> {
+    template
+        .split("${")
+        .skip(1)
+        .filter_map(|part| part.find('}').map(|end| &part[..end]))
+        .collect()
+}
+
+/// Replaces environment variables of a template by their values, e.g. `${MY_KEY}`
+fn interpolate(template: &str) -> AppResult<String> {
+    env_names(template).iter().try_fold(template.to_string(), |text, name| {
+        Ok(text.replace(&format!("${{{}}}", name), &get_env(name)?))
+    })
+}
+
+impl Api for CustomApi {
+    fn get_name(&self) -> &str {
+        NAME
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            multiple_fiats: false,
+            ..Capabilities::default()
+        }
+    }
+
+    fn get_endpoint(&self) -> &str {
+        &self.settings.url
+    }
+
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let response = self.request(symbols, fiats)?;
+        self.parse(&response.body, symbols, fiats)
+    }
+}
+
+impl RawApi for CustomApi {
+    fn request(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<RawResponse> {
+        // fiats are requested one by one (see `multiple_fiats`), unless this api is used without `CrossRateApi`
+        let template = self.settings.url.replace("{symbols}", &symbols.join(",")).replace("{fiat}", &fiats.join(","));
+        // the template is logged, because values of environment variables are secrets like api keys
+        info!("fetch custom url {}", template);
+        let url = interpolate(&template)?;
+
+        let headers = self
+            .settings
+            .headers
+            .iter()
+            .map(|(name, value)| Ok((name.as_str(), interpolate(value)?)))
+            .collect::<AppResult<Vec<_>>>()?;
+        let body = self.client.get(&url, &headers)?;
+        Ok(RawResponse { url, body })
+    }
+
+    fn parse(&self, body: &str, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let response: Value = serde_json::from_str(body)?;
+        let settings = &self.settings;
+        let coin_list = symbols
+            .iter()
+            .map(|symbol| {
+                let quotes = fiats
+                    .iter()
+                    .filter_map(|fiat| {
+                        // a quote without price is left out, like a symbol not found
+                        let quote = types::Quote {
+                            price: Some(self.select(&response, Some(&settings.price), symbol, fiat)?),
+                            percent_change_24h: self.select(
+                                &response,
+                                settings.percent_change_24h.as_ref(),
+                                symbol,
+                                fiat,
+                            ),
+                            market_cap: self.select(&response, settings.market_cap.as_ref(), symbol, fiat),
+                            ..types::Quote::default()
+                        };
+                        Some((fiat.to_string(), quote))
+                    })
+                    .collect();
+                types::Coin {
+                    symbol: symbol.to_string(),
+                    quotes,
+                    supply: None,
+                }
+            })
+            .filter(|coin| !coin.quotes.is_empty())
+            .collect();
+        info!("details {:?}", coin_list);
+        Ok(Coins::new(coin_list))
+    }
+
+    fn secrets(&self) -> Vec<String> {
+        let templates = self.settings.headers.values().chain(Some(&self.settings.url));
+        templates
+            .flat_map(|t| env_names(t))
+            .filter_map(|name| get_env(name).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_json::json;
+
+    fn settings() -> CustomSettings {
+        CustomSettings {
+            url: "https://example.com/quotes?symbols={symbols}&convert={fiat}&key=${WTCH_CRPTS_TEST_KEY}".into(),
+            headers: BTreeMap::new(),
+            price: "$.data[?(@.symbol=='{symbol}')].quote.{fiat}.price".into(),
+            percent_change_24h: Some("$.data[?(@.symbol=='{symbol}')].quote.{fiat}.change".into()),
+            market_cap: None,
+        }
+    }
+
+    #[test]
+    fn parse_custom_response() {
+        let body = json!({"data": [
+            {"symbol": "BTC", "quote": {"EUR": {"price": 3200.5, "change": "-1.25"}, "USD": {"price": "3600"}}},
+            {"symbol": "ETH", "quote": {"EUR": {"change": 2}}}
+        ]});
+        let api = CustomApi::new(settings(), None).unwrap();
+        let coins = api.parse(&body.to_string(), &["BTC", "ETH"], &["EUR", "USD"]).unwrap();
+        assert_eq!(coins.list.len(), 1);
+        let btc = &coins.list[0];
+        assert_eq!(btc.symbol, "BTC");
+        let eur = btc.quote("EUR").unwrap();
+        assert_eq!(eur.price, Some("3200.5".parse().unwrap()));
+        assert_eq!(eur.percent_change_24h, Some("-1.25".parse().unwrap()));
+        assert_eq!(eur.market_cap, None);
+        assert_eq!(btc.price("USD"), Some(&"3600".parse().unwrap()));
+    }
+
+    #[test]
+    fn interpolate_env() {
+        std::env::set_var("WTCH_CRPTS_TEST_KEY", "secret");
+        assert_eq!(env_names("a ${X} b ${Y_Z}"), vec!["X", "Y_Z"]);
+        assert_eq!(interpolate("Bearer ${WTCH_CRPTS_TEST_KEY}").unwrap(), "Bearer secret");
+        assert_eq!(interpolate("no variables").unwrap(), "no variables");
+        assert_eq!(interpolate("${WTCH_CRPTS_MISSING}").unwrap_err().kind(), "env");
+        let api = CustomApi::new(settings(), None).unwrap();
+        assert_eq!(api.secrets(), vec!["secret".to_string()]);
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let invalid_path = CustomSettings {
+            price: "data.price".into(),
+            ..settings()
+        };
+        assert_eq!(CustomApi::new(invalid_path, None).err().map(|e| e.kind()), Some("json_path"));
+        let empty_url = CustomSettings {
+            url: "".into(),
+            ..settings()
+        };
+        assert_eq!(CustomApi::new(empty_url, None).err().map(|e| e.kind()), Some("custom_provider"));
+        let endpoint = "http://localhost:3000";
+        assert_eq!(CustomApi::new(settings(), Some(endpoint)).unwrap().get_endpoint(), endpoint);
+        let missing = create(None, &ConfigFile::default());
+        assert_eq!(missing.err().map(|e| e.kind()), Some("custom_provider"));
+    }
+}
@@ -0,0 +1,336 @@
+use chrono::{DateTime, Local};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::{Api, Capabilities, RawApi};
+use crate::app::{
+    errors::AppError,
+    types::{AppResult, Coins, Quote},
+};
+
+// Directory of fixtures replayed in development mode if no other directory is given
+pub const DEFAULT_FIXTURES_DIR: &str = "mock";
+
+const SCRUBBED: &str = "SCRUBBED";
+
+/// Recorded response of an api provider
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Fixture {
+    provider: String,
+    url: String,
+    recorded_at: DateTime<Local>,
+    response: serde_json::Value,
+}
+
+/// Path of a fixture, e.g. `mock/coinmarketcap-EUR.json` or `mock/coinmarketcap-EUR-USD.json`
+fn fixture_path(dir: &Path, provider: &str, fiats: &[&str]) -> PathBuf {
+    dir.join(format!("{}-{}.json", provider, fiats.join("-")))
+}
+
+fn fixture_error(path: &Path) -> impl Fn(std::io::Error) -> AppError {
+    let path = path.display().to_string();
+    move |cause| AppError::Fixture {
+        path: path.clone(),
+        cause,
+    }
+}
+
+/// Recorded response of an api provider in given fiats
+pub fn read_fixture(dir: &Path, provider: &str, fiats: &[&str]) -> AppResult<serde_json::Value> {
+    let path = fixture_path(dir, provider, fiats);
+    info!("replay {}", path.display());
+    let content = fs::read_to_string(&path).map_err(fixture_error(&path))?;
+    let fixture: Fixture = serde_json::from_str(&content)?;
+    Ok(fixture.response)
+}
+
+fn is_not_found(error: &AppError) -> bool {
+    match error {
+        AppError::Fixture { cause, .. } => cause.kind() == ErrorKind::NotFound,
+        _ => false,
+    }
+}
+
+/// Fiats of any other fixture of an api provider, e.g. `["EUR"]` of `mock/coinmarketcap-EUR.json`
+fn other_fixture_fiats(dir: &Path, provider: &str) -> Option<Vec<String>> {
+    let prefix = format!("{}-", provider);
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|name| Some(name.strip_prefix(&prefix)?.strip_suffix(".json")?.to_string()))
+        // databases of `json-server` aren't recorded responses
+        .filter(|name| name != "api")
+        .collect();
+    names.sort();
+    names.first().map(|name| name.split('-').map(String::from).collect())
+}
+
+/// Response of a route of a `json-server` database, e.g. `quotes` of `mock/coinmarketcap-api.json`
+#[cfg(test)]
+pub fn read_api_db(dir: &Path, provider: &str, route: &str) -> AppResult<serde_json::Value> {
+    let path = dir.join(format!("{}-api.json", provider));
+    let content = fs::read_to_string(&path).map_err(fixture_error(&path))?;
+    let mut db: serde_json::Value = serde_json::from_str(&content)?;
+    match db.get_mut(route) {
+        Some(response) => Ok(response.take()),
+        None => Err(AppError::ApiParseMap { key: route.into() }),
+    }
+}
+
+/// Replaces all secrets (e.g. api keys) in a text
+fn scrub(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .filter(|s| !s.is_empty())
+        .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), SCRUBBED))
+}
+
+/// Api wrapper, which stores every response of an api provider as a fixture with all secrets scrubbed
+pub struct RecordingApi {
+    api: Box<dyn RawApi>,
+    dir: PathBuf,
+}
+
+impl RecordingApi {
+    pub fn new(api: Box<dyn RawApi>, dir: PathBuf) -> Self {
+        RecordingApi { api, dir }
+    }
+
+    fn record(&self, url: &str, body: &str, fiats: &[&str]) -> AppResult<()> {
+        let secrets = self.api.secrets();
+        let fixture = Fixture {
+            provider: self.api.get_name().into(),
+            url: scrub(url, &secrets),
+            recorded_at: Local::now(),
+            response: serde_json::from_str(&scrub(body, &secrets))?,
+        };
+        let path = fixture_path(&self.dir, self.api.get_name(), fiats);
+        fs::create_dir_all(&self.dir).map_err(fixture_error(&path))?;
+        fs::write(&path, serde_json::to_string_pretty(&fixture)?).map_err(fixture_error(&path))?;
+        info!("recorded {}", path.display());
+        Ok(())
+    }
+}
+
+impl Api for RecordingApi {
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let response = self.api.request(symbols, fiats)?;
+        // a response, which can't be recorded, is still used
+        if let Err(e) = self.record(&response.url, &response.body, fiats) {
+            warn!("failed to record response {}", e);
+        }
+        self.api.parse(&response.body, symbols, fiats)
+    }
+
+    fn get_endpoint(&self) -> &str {
+        self.api.get_endpoint()
+    }
+
+    fn get_name(&self) -> &str {
+        self.api.get_name()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.api.capabilities()
+    }
+}
+
+/// Api provider, which replays recorded responses of another api provider without any requests
+pub struct ReplayApi {
+    api: Box<dyn RawApi>,
+    dir: PathBuf,
+    name: String,
+}
+
+impl ReplayApi {
+    pub fn new(api: Box<dyn RawApi>, dir: PathBuf) -> Self {
+        let name = format!("{}-replay", api.get_name());
+        ReplayApi { api, dir, name }
+    }
+
+    /// Coins of the fixture of given fiats, or of the fixtures of each fiat merged. Without a fixture of a fiat,
+    /// quotes of any other fixture of the provider are used, like a mock server responds the same to every request.
+    fn replay(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let provider = self.api.get_name();
+        let missing = match read_fixture(&self.dir, provider, fiats) {
+            Ok(response) => return self.api.parse(&response.to_string(), symbols, fiats),
+            Err(e) => e,
+        };
+        if !is_not_found(&missing) {
+            return Err(missing);
+        }
+        if fiats.len() > 1 {
+            let mut coins = Coins::new(vec![]);
+            for fiat in fiats {
+                coins.merge(self.replay(symbols, &[fiat])?);
+            }
+            coins.list.sort_by_key(|c| symbols.iter().position(|s| *s == c.symbol));
+            return Ok(coins);
+        }
+        let other = other_fixture_fiats(&self.dir, provider).ok_or(missing)?;
+        warn!("no fixture of {} in {}, replay {} instead", fiats.join("-"), self.dir.display(), other.join("-"));
+        let other: Vec<&str> = other.iter().map(String::as_str).collect();
+        let response = read_fixture(&self.dir, provider, &other)?;
+        let mut coins = self.api.parse(&response.to_string(), symbols, &other)?;
+        for coin in &mut coins.list {
+            // values formatted by the provider show the other fiat
+            let quote = coin.quote(other[0]).cloned().map(|q| Quote { formatted: None, ..q });
+            coin.quotes = fiats.iter().filter_map(|f| Some((f.to_string(), quote.clone()?))).collect();
+        }
+        Ok(coins)
+    }
+}
+
+impl Api for ReplayApi {
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+        let mut coins = self.replay(symbols, fiats)?;
+        // replayed responses don't cost anything
+        coins.credit_count = None;
+        Ok(coins)
+    }
+
+    fn get_endpoint(&self) -> &str {
+        self.dir.to_str().unwrap_or_default()
+    }
+
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Replayed responses need neither a key nor credits
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            credits: false,
+            key_env: None,
+            ..self.api.capabilities()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::api::{coinmarketcap::CoinMarketCap, cryptocompare::CryptoCompare, RawResponse};
+    use crate::app::decimal::Decimal;
+
+    /// Api provider responding with a fixed body, which contains its api key
+    struct SecretApi;
+
+    impl Api for SecretApi {
+        fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins> {
+            self.parse(&self.request(symbols, fiats)?.body, symbols, fiats)
+        }
+
+        fn get_endpoint(&self) -> &str {
+            "http://localhost"
+        }
+
+        fn get_name(&self) -> &str {
+            "secret"
+        }
+    }
+
+    impl RawApi for SecretApi {
+        fn request(&self, _symbols: &[&str], _fiats: &[&str]) -> AppResult<RawResponse> {
+            Ok(RawResponse {
+                url: "http://localhost/quotes?api_key=abc123".into(),
+                body: r#"{"key": "abc123", "coins": [{"symbol": "BTC", "quotes": {"EUR": {"price": 3200.5}}}]}"#.into(),
+            })
+        }
+
+        fn parse(&self, body: &str, _symbols: &[&str], _fiats: &[&str]) -> AppResult<Coins> {
+            let value: serde_json::Value = serde_json::from_str(body)?;
+            Ok(Coins::new(serde_json::from_value(value["coins"].clone())?))
+        }
+
+        fn secrets(&self) -> Vec<String> {
+            vec!["abc123".into()]
+        }
+    }
+
+    fn mock_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_FIXTURES_DIR)
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let dir = std::env::temp_dir().join(format!("wtch-crpts-test-fixtures-{}", std::process::id()));
+        let recording = RecordingApi::new(Box::new(SecretApi), dir.clone());
+        let recorded = recording.get_coin_details(&["BTC"], &["EUR"]).unwrap();
+        assert_eq!(recorded.list[0].price("EUR"), Some(&Decimal::from(3200.5)));
+
+        let content = fs::read_to_string(dir.join("secret-EUR.json")).unwrap();
+        assert!(!content.contains("abc123"));
+        assert!(content.contains("api_key=SCRUBBED"));
+
+        let replay = ReplayApi::new(Box::new(SecretApi), dir.clone());
+        assert_eq!(replay.get_name(), "secret-replay");
+        assert_eq!(replay.get_coin_details(&["BTC"], &["EUR"]).unwrap().list, recorded.list);
+        // quotes of other fiats are used without fixture
+        let replayed = replay.get_coin_details(&["BTC"], &["USD"]).unwrap();
+        assert_eq!(replayed.list[0].price("USD"), Some(&Decimal::from(3200.5)));
+        let unknown = ReplayApi::new(Box::new(SecretApi), mock_dir()).get_coin_details(&["BTC"], &["EUR"]);
+        assert_eq!(unknown.err().map(|e| e.kind()), Some("fixture"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn replay_fixtures_per_fiat() {
+        let dir = std::env::temp_dir().join(format!("wtch-crpts-test-fiat-fixtures-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (fiat, price) in &[("EUR", 3200.5), ("USD", 3600.0)] {
+            let quotes = format!(r#"{{"{}": {{"price": {}}}}}"#, fiat, price);
+            let body = format!(r#"{{"coins": [{{"symbol": "BTC", "quotes": {}}}]}}"#, quotes);
+            let fixture = Fixture {
+                provider: "secret".into(),
+                url: "http://localhost/quotes".into(),
+                recorded_at: Local::now(),
+                response: serde_json::from_str(&body).unwrap(),
+            };
+            fs::write(fixture_path(&dir, "secret", &[fiat]), serde_json::to_string(&fixture).unwrap()).unwrap();
+        }
+        let replay = ReplayApi::new(Box::new(SecretApi), dir.clone());
+        let coins = replay.get_coin_details(&["BTC"], &["EUR", "USD"]).unwrap();
+        assert_eq!(coins.get_symbols(), vec!["BTC"]);
+        assert_eq!(coins.list[0].price("EUR"), Some(&Decimal::from(3200.5)));
+        assert_eq!(coins.list[0].price("USD"), Some(&Decimal::from(3600.0)));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn replay_mock_data() {
+        let cmc = ReplayApi::new(Box::new(CoinMarketCap::new(None)), mock_dir());
+        let coins = cmc.get_coin_details(&["BTC", "LTC"], &["EUR"]).unwrap();
+        assert_eq!(coins.get_symbols(), vec!["BTC"]);
+        assert_eq!(coins.credit_count, None);
+        // the default fiat is replayed without recording it
+        let usd = cmc.get_coin_details(&["BTC"], &["USD"]).unwrap();
+        assert_eq!(usd.list[0].price("USD"), coins.list[0].price("EUR"));
+
+        let cryptocompare = ReplayApi::new(Box::new(CryptoCompare::new(None)), mock_dir());
+        let coins = cryptocompare.get_coin_details(&["BTC", "XYZ"], &["EUR"]).unwrap();
+        assert_eq!(coins.get_symbols(), vec!["BTC"]);
+    }
+
+    #[test]
+    fn parse_api_db() {
+        let response = read_api_db(&mock_dir(), "coinmarketcap", "quotes").unwrap();
+        let coins = CoinMarketCap::new(None).parse(&response.to_string(), &["BTC", "LTC"], &["EUR"]).unwrap();
+        assert_eq!(coins.get_symbols(), vec!["BTC", "LTC"]);
+        assert_eq!(coins.list[1].price("EUR"), Some(&Decimal::from(1)));
+
+        let response = read_api_db(&mock_dir(), "cryptocompare", "quotes").unwrap();
+        let coins = CryptoCompare::new(None).parse(&response.to_string(), &["BTC", "LTC"], &["EUR"]).unwrap();
+        assert_eq!(coins.list[0].price("EUR"), Some(&"3200.98".parse().unwrap()));
+
+        let missing = read_api_db(&mock_dir(), "cryptocompare", "listings");
+        assert_eq!(missing.err().map(|e| e.kind()), Some("api_parse_map"));
+    }
+
+    #[test]
+    fn scrub_secrets() {
+        assert_eq!(scrub("a=1&key=xyz", &["xyz".into(), "".into()]), "a=1&key=SCRUBBED");
+    }
+}
@@ -0,0 +1,292 @@
+use chrono::{DateTime, Utc};
+use reqwest::{header::RETRY_AFTER, StatusCode};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::app::{errors::AppError, types::AppResult};
+
+const DEFAULT_CONNECT_TIMEOUT: u64 = 10;
+const DEFAULT_READ_TIMEOUT: u64 = 30;
+const DEFAULT_RETRIES: u32 = 2;
+const DEFAULT_BACKOFF: u64 = 500;
+const DEFAULT_RETRY_BUDGET: u64 = 10;
+// Longest delay between retries, even if a provider asks to wait longer
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+// Max. characters of a response body shown in errors
+const MAX_ERROR_BODY: usize = 200;
+
+/// Settings of http requests to api providers stored in config file, e.g.
+///
+/// ```toml
+/// [http]
+/// connect_timeout = 5
+/// read_timeout = 20
+/// retries = 3
+/// backoff = 1000
+/// retry_budget = 5
+/// ```
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct HttpSettings {
+    /// Seconds to connect to a provider
+    pub connect_timeout: Option<u64>,
+    /// Seconds to receive a whole response
+    pub read_timeout: Option<u64>,
+    /// Retries of transient failures, `0` to fail on the first one
+    pub retries: Option<u32>,
+    /// Milliseconds to wait before the first retry, which are doubled by every further retry
+    pub backoff: Option<u64>,
+    /// Seconds a request may take including all retries, which keeps the UI responsive between refreshes.
+    /// A timed out request is retried only if its timeout is shorter than the budget.
+    pub retry_budget: Option<u64>,
+}
+
+impl HttpSettings {
+    /// Settings whose retry budget is at most half of the refresh interval, so retries end before the next refresh
+    pub fn within_interval(self, interval: Duration) -> Self {
+        let budget = self.retry_budget.unwrap_or(DEFAULT_RETRY_BUDGET).min(interval.as_secs() / 2);
+        HttpSettings {
+            retry_budget: Some(budget),
+            ..self
+        }
+    }
+}
+
+/// Http client shared by api providers, which retries transient failures (connection errors, 5xx and 429)
+pub struct HttpClient {
+    client: reqwest::Client,
+    retries: u32,
+    backoff: Duration,
+    retry_budget: Duration,
+}
+
+/// Failed attempt of a request
+struct Failed {
+    error: AppError,
+    transient: bool,
+    /// Delay a provider asked for, e.g. by `Retry-After: 60`
+    retry_after: Option<Duration>,
+}
+
+impl Failed {
+    fn from_error(error: reqwest::Error, url: &str) -> Self {
+        if error.is_timeout() {
+            // retried like other transient failures, but only if the timeout left some of the retry budget
+            return Failed {
+                error: AppError::ApiTimeout { url: without_query(url) },
+                transient: true,
+                retry_after: None,
+            };
+        }
+        Failed {
+            transient: error.is_http(),
+            error: AppError::ApiRequest(error),
+            retry_after: None,
+        }
+    }
+}
+
+impl HttpClient {
+    pub fn new(settings: &HttpSettings) -> AppResult<Self> {
+        let connect_timeout = settings.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT);
+        let read_timeout = settings.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT);
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(connect_timeout))
+            .timeout(Duration::from_secs(read_timeout))
+            .build()
+            .map_err(AppError::ApiRequest)?;
+        Ok(HttpClient {
+            client,
+            retries: settings.retries.unwrap_or(DEFAULT_RETRIES),
+            backoff: Duration::from_millis(settings.backoff.unwrap_or(DEFAULT_BACKOFF)),
+            retry_budget: Duration::from_secs(settings.retry_budget.unwrap_or(DEFAULT_RETRY_BUDGET)),
+        })
+    }
+
+    /// Body of a successful response to a GET request, which is retried on transient failures
+    /// as long as the retry budget isn't used up
+    pub fn get(&self, url: &str, headers: &[(&str, String)]) -> AppResult<String> {
+        let started = Instant::now();
+        let mut attempt = 0;
+        loop {
+            let failed = match self.send(url, headers) {
+                Ok(body) => return Ok(body),
+                Err(failed) => failed,
+            };
+            if !failed.transient || attempt >= self.retries {
+                return Err(failed.error);
+            }
+            let delay = match failed.retry_after {
+                Some(delay) => delay.min(MAX_BACKOFF),
+                None => backoff(attempt, self.backoff, rand::random()),
+            };
+            if started.elapsed() + delay > self.retry_budget {
+                warn!("request to {} failed ({}), retry budget is used up", without_query(url), failed.error);
+                return Err(failed.error);
+            }
+            warn!("request to {} failed ({}), retry in {:?}", without_query(url), failed.error, delay);
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    fn send(&self, url: &str, headers: &[(&str, String)]) -> Result<String, Failed> {
+        let request = headers
+            .iter()
+            .fold(self.client.get(url), |request, (name, value)| request.header(*name, value.as_str()));
+        let mut response = request.send().map_err(|e| Failed::from_error(e, url))?;
+        let status = response.status();
+        if status.is_success() {
+            return response.text().map_err(|e| Failed::from_error(e, url));
+        }
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, Utc::now()));
+        Err(Failed {
+            error: AppError::ApiRejected {
+                url: without_query(url),
+                status: status.as_u16(),
+                body: truncate(&response.text().unwrap_or_default(), MAX_ERROR_BODY),
+            },
+            transient: status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            retry_after,
+        })
+    }
+}
+
+impl Default for HttpClient {
+    /// Client using default timeouts and retries, which panics like `reqwest::Client::new` if TLS can't be initialized
+    fn default() -> Self {
+        HttpClient::new(&HttpSettings::default()).expect("failed to create http client")
+    }
+}
+
+/// Exponential delay before a retry with "equal jitter", i.e. half of it is random in `[0, 1)`
+fn backoff(attempt: u32, base: Duration, random: f64) -> Duration {
+    let delay = base
+        .checked_mul(2u32.saturating_pow(attempt))
+        .map_or(MAX_BACKOFF, |delay| delay.min(MAX_BACKOFF));
+    delay / 2 + delay.mul_f64(random) / 2
+}
+
+/// Delay of header `Retry-After`, which is given in seconds or as http date
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let time = DateTime::parse_from_rfc2822(value).ok()?;
+            // a date in the past means to retry right away
+            Some((time.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+        }
+    }
+}
+
+/// Text cut off after given characters, e.g. a long response body in an error
+fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.into(),
+    }
+}
+
+/// Url without query, which may contain api keys, e.g. to show it in errors
+fn without_query(url: &str) -> String {
+    url.split('?').next().unwrap_or(url).into()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use chrono::TimeZone;
+    use std::net::TcpListener;
+
+    #[test]
+    fn backoff_exponentially_with_jitter() {
+        let base = Duration::from_millis(500);
+        assert_eq!(backoff(0, base, 0.0), Duration::from_millis(250));
+        assert_eq!(backoff(0, base, 0.5), Duration::from_millis(375));
+        assert_eq!(backoff(2, base, 0.0), Duration::from_millis(1000));
+        assert!(backoff(2, base, 0.999) < Duration::from_millis(2000));
+        assert_eq!(backoff(10, base, 0.0), MAX_BACKOFF / 2);
+        assert_eq!(backoff(40, base, 0.0), MAX_BACKOFF / 2);
+    }
+
+    #[test]
+    fn parse_retry_after_header() {
+        let now = Utc.with_ymd_and_hms(2019, 2, 9, 17, 34, 23).unwrap();
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Sat, 09 Feb 2019 17:35:23 GMT", now), Some(Duration::from_secs(60)));
+        assert_eq!(parse_retry_after("Sat, 09 Feb 2019 17:00:00 GMT", now), Some(Duration::from_secs(0)));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn hide_query_of_urls() {
+        assert_eq!(without_query("https://example.com/data?api_key=secret"), "https://example.com/data");
+        assert_eq!(without_query("https://example.com/data"), "https://example.com/data");
+    }
+
+    #[test]
+    fn limit_retry_budget_by_interval() {
+        let settings = HttpSettings::default().within_interval(Duration::from_secs(60));
+        assert_eq!(settings.retry_budget, Some(DEFAULT_RETRY_BUDGET));
+        let settings = HttpSettings::default().within_interval(Duration::from_secs(6));
+        assert_eq!(settings.retry_budget, Some(3));
+    }
+
+    #[test]
+    fn truncate_bodies() {
+        assert_eq!(truncate("error", 10), "error");
+        assert_eq!(truncate("äöü error", 3), "äöü…");
+    }
+
+    #[test]
+    fn fail_after_retries() {
+        let settings = HttpSettings {
+            connect_timeout: Some(1),
+            retries: Some(1),
+            backoff: Some(1),
+            ..HttpSettings::default()
+        };
+        // nothing listens on port 1, which is a transient failure
+        let result = HttpClient::new(&settings).unwrap().get("http://127.0.0.1:1/data", &[]);
+        assert_eq!(result.err().map(|e| e.kind()), Some("api_request"));
+    }
+
+    #[test]
+    fn fail_without_retry_budget() {
+        let settings = HttpSettings {
+            connect_timeout: Some(1),
+            retries: Some(5),
+            backoff: Some(60_000),
+            retry_budget: Some(1),
+            ..HttpSettings::default()
+        };
+        let started = Instant::now();
+        let result = HttpClient::new(&settings).unwrap().get("http://127.0.0.1:1/data", &[]);
+        assert_eq!(result.err().map(|e| e.kind()), Some("api_request"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn retry_timeouts() {
+        // accepts connections, but never responds
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/data", listener.local_addr().unwrap());
+        // connections are returned to keep them open
+        let accepted = thread::spawn(move || listener.incoming().take(2).collect::<Vec<_>>());
+        let settings = HttpSettings {
+            read_timeout: Some(1),
+            retries: Some(1),
+            backoff: Some(1),
+            ..HttpSettings::default()
+        };
+        let result = HttpClient::new(&settings).unwrap().get(&url, &[]);
+        assert_eq!(result.err().map(|e| e.kind()), Some("api_timeout"));
+        assert_eq!(accepted.join().unwrap().len(), 2);
+    }
+}
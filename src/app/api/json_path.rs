@@ -0,0 +1,137 @@
+use serde_json::Value;
+use std::str::FromStr;
+
+use crate::app::errors::AppError;
+
+/// Step from a json value to one of its children
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    /// First element of an array, whose field has given value, e.g. `[?(@.symbol=='BTC')]`
+    Filter { field: String, value: String },
+}
+
+/// Path to a value of a json document, e.g. `$.data.BTC.price` or `$.coins[?(@.symbol=='BTC')].price`.
+/// Steps are keys (`.key` or `['key']`), indices (`[0]`) and filters of arrays by a field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    steps: Vec<Step>,
+}
+
+impl FromStr for JsonPath {
+    type Err = AppError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let error = |reason: &str| AppError::JsonPath {
+            path: path.into(),
+            reason: reason.into(),
+        };
+        let mut rest = path.trim().strip_prefix('$').ok_or_else(|| error("has to start with $"))?;
+        let mut steps = Vec::new();
+        while !rest.is_empty() {
+            if let Some(next) = rest.strip_prefix('.') {
+                let end = next.find(['.', '[']).unwrap_or(next.len());
+                if end == 0 {
+                    return Err(error("key is empty"));
+                }
+                steps.push(Step::Key(next[..end].into()));
+                rest = &next[end..];
+            } else if let Some(next) = rest.strip_prefix('[') {
+                let end = next.find(']').ok_or_else(|| error("] is missing"))?;
+                steps.push(bracket_step(&next[..end]).ok_or_else(|| error("invalid step in brackets"))?);
+                rest = &next[end + 1..];
+            } else {
+                return Err(error("steps have to start with . or ["));
+            }
+        }
+        Ok(JsonPath { steps })
+    }
+}
+
+/// Step written in brackets, e.g. `'key'`, `0` or `?(@.symbol=='BTC')`
+fn bracket_step(inner: &str) -> Option<Step> {
+    if let Some(filter) = inner.strip_prefix("?(@.").and_then(|f| f.strip_suffix(')')) {
+        let mut parts = filter.splitn(2, "==");
+        let field = parts.next()?.trim();
+        let value = unquote(parts.next()?.trim())?;
+        Some(Step::Filter {
+            field: field.into(),
+            value: value.into(),
+        })
+    } else if let Some(key) = unquote(inner) {
+        Some(Step::Key(key.into()))
+    } else {
+        inner.trim().parse().ok().map(Step::Index)
+    }
+}
+
+fn unquote(text: &str) -> Option<&str> {
+    ['\'', '"']
+        .iter()
+        .find_map(|quote| text.strip_prefix(*quote).and_then(|t| t.strip_suffix(*quote)))
+}
+
+/// Value matches a value of a filter, e.g. `"BTC"` or `1` matches `'1'`
+fn is_match(value: Option<&Value>, expected: &str) -> bool {
+    match value {
+        Some(Value::String(s)) => s == expected,
+        Some(Value::Number(n)) => n.as_f64().is_some() && n.as_f64() == expected.parse().ok(),
+        Some(Value::Bool(b)) => expected.parse() == Ok(*b),
+        _ => false,
+    }
+}
+
+impl JsonPath {
+    /// Value at this path, or `None` if any step is missing
+    pub fn select<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.steps.iter().try_fold(value, |v, step| match step {
+            Step::Key(key) => v.get(key.as_str()),
+            Step::Index(index) => v.get(*index),
+            Step::Filter { field, value } => v
+                .as_array()?
+                .iter()
+                .find(|element| is_match(element.get(field.as_str()), value)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use serde_json::json;
+
+    fn select(path: &str, value: &Value) -> Option<Value> {
+        path.parse::<JsonPath>().unwrap().select(value).cloned()
+    }
+
+    #[test]
+    fn select_values() {
+        let value = json!({
+            "data": {"BTC": {"quote": {"EUR": {"price": 3200.98}}}},
+            "coins": [{"symbol": "ETH", "rank": 2, "price": 100, "active": true}, {"symbol": "BTC", "rank": 1, "price": 3200}],
+            "odd key": ["a", "b"]
+        });
+        assert_eq!(select("$.data.BTC.quote.EUR.price", &value), Some(json!(3200.98)));
+        assert_eq!(select("$['data'][\"BTC\"].quote", &value), Some(json!({"EUR": {"price": 3200.98}})));
+        assert_eq!(select("$.coins[1].symbol", &value), Some(json!("BTC")));
+        assert_eq!(select("$.coins[?(@.symbol=='BTC')].price", &value), Some(json!(3200)));
+        assert_eq!(select("$.coins[?(@.rank == '2')].symbol", &value), Some(json!("ETH")));
+        assert_eq!(select("$.coins[?(@.rank == '1.0')].symbol", &value), Some(json!("BTC")));
+        assert_eq!(select("$.coins[?(@.active == 'true')].symbol", &value), Some(json!("ETH")));
+        assert_eq!(select("$['odd key'][0]", &value), Some(json!("a")));
+        assert_eq!(select("$", &value), Some(value.clone()));
+        assert_eq!(select("$.data.LTC.quote", &value), None);
+        assert_eq!(select("$.coins[5]", &value), None);
+        assert_eq!(select("$.coins[?(@.symbol=='XYZ')].price", &value), None);
+    }
+
+    #[test]
+    fn parse_invalid_paths() {
+        for path in &["data.BTC", "$.", "$.data..BTC", "$.coins[", "$.coins[x]", "$data", "$.coins[?(@.symbol)]"] {
+            let error = path.parse::<JsonPath>().unwrap_err();
+            assert_eq!(error.kind(), "json_path", "{} is valid", path);
+        }
+    }
+}
@@ -1,9 +1,131 @@
-use super::types::{AppResult, Coins};
+use super::{
+    constants::FIAT_LIST,
+    env::get_env,
+    errors::AppError,
+    types::{AppResult, Coins},
+};
 
+pub mod cache;
 pub mod coinmarketcap;
+pub mod cross;
 pub mod cryptocompare;
+pub mod custom;
+pub mod fixture;
+pub mod http;
+pub mod json_path;
+pub mod registry;
 
 pub trait Api {
-    fn get_coin_details(&self, symbols: &[&str], fiat: &str) -> AppResult<Coins>;
+    fn get_coin_details(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins>;
     fn get_endpoint(&self) -> &str;
+    fn get_name(&self) -> &str;
+    /// Features of the api provider, e.g. to validate args or to hide what it doesn't support
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+}
+
+/// Features supported by an api provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct Capabilities {
+    /// Fiat currencies quoted by the provider
+    pub fiats: &'static [&'static str],
+    /// Cryptocurrencies are quoted directly, otherwise they're derived via cross rates
+    pub crypto_quotes: bool,
+    /// Quotes in multiple fiats are fetched by a single request
+    pub multiple_fiats: bool,
+    /// Historical rates are fetched from the provider. No provider does yet, history is stored locally instead.
+    pub history: bool,
+    /// Coins are listed by the provider, e.g. by market cap. No provider does yet, coins are given by symbols.
+    pub listings: bool,
+    /// Updates are pushed by the provider, e.g. via websocket. No provider does yet, rates are polled.
+    pub streaming: bool,
+    /// Provider reports used api credits
+    pub credits: bool,
+    /// Environment variable of the api key, if the provider needs one
+    pub key_env: Option<&'static str>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Capabilities {
+            fiats: &FIAT_LIST,
+            crypto_quotes: false,
+            multiple_fiats: true,
+            history: false,
+            listings: false,
+            streaming: false,
+            credits: false,
+            key_env: None,
+        }
+    }
+}
+
+impl Capabilities {
+    /// Fails on fiats the provider doesn't quote. Other symbols (e.g. BTC) are cryptocurrencies,
+    /// which are quoted directly or derived via cross rates.
+    pub fn check_fiats(&self, provider: &str, fiats: &[&str]) -> AppResult<()> {
+        match fiats.iter().find(|f| FIAT_LIST.contains(f) && !self.fiats.contains(f)) {
+            Some(fiat) => Err(AppError::UnsupportedFiat {
+                provider: provider.into(),
+                fiat: fiat.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails if the provider needs an api key, which isn't set
+    pub fn check_key(&self, provider: &str) -> AppResult<()> {
+        match self.key_env {
+            Some(name) if get_env(name).map(|key| key.is_empty()).unwrap_or(true) => Err(AppError::ApiKey {
+                provider: provider.into(),
+                name: name.into(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Response of an api provider as it's sent, e.g. to record it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawResponse {
+    pub url: String,
+    pub body: String,
+}
+
+/// Api provider, which requests and parses responses in separate steps
+pub trait RawApi: Api {
+    fn request(&self, symbols: &[&str], fiats: &[&str]) -> AppResult<RawResponse>;
+    fn parse(&self, body: &str, symbols: &[&str], fiats: &[&str]) -> AppResult<Coins>;
+    /// Values (e.g. api keys), which must not be recorded
+    fn secrets(&self) -> Vec<String>;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn check_fiats() {
+        let capabilities = Capabilities {
+            fiats: &["USD", "EUR"],
+            ..Capabilities::default()
+        };
+        assert!(capabilities.check_fiats("test", &["EUR", "USD"]).is_ok());
+        // cryptocurrencies are derived, if they aren't quoted directly
+        assert!(capabilities.check_fiats("test", &["EUR", "BTC"]).is_ok());
+        let error = capabilities.check_fiats("test", &["USD", "JPY"]).unwrap_err();
+        assert_eq!(error.to_string(), "Provider test doesn't support fiat JPY");
+    }
+
+    #[test]
+    fn check_key() {
+        let capabilities = Capabilities {
+            key_env: Some("WTCH_CRPTS_TEST_MISSING_KEY"),
+            ..Capabilities::default()
+        };
+        assert_eq!(capabilities.check_key("test").unwrap_err().kind(), "api_key");
+        assert!(Capabilities::default().check_key("test").is_ok());
+    }
 }
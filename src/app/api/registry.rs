@@ -0,0 +1,89 @@
+use super::{coinmarketcap, cryptocompare, custom, RawApi};
+use crate::app::{config::ConfigFile, errors::AppError, types::AppResult};
+
+/// Creates an api provider using its public endpoint, or any other endpoint (e.g. of `mock-server`).
+/// Providers may read their settings from the config file, e.g. `custom`.
+pub type Constructor = fn(Option<&str>, &ConfigFile) -> AppResult<Box<dyn RawApi>>;
+
+/// Api provider known to the app, which is registered in `PROVIDERS`
+pub struct Provider {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Environment variables, which have to be set to send requests, e.g. api keys
+    pub env_keys: &'static [&'static str],
+    pub constructor: Constructor,
+}
+
+impl Provider {
+    pub fn create(&self, endpoint: Option<&str>, config_file: &ConfigFile) -> AppResult<Box<dyn RawApi>> {
+        (self.constructor)(endpoint, config_file)
+    }
+}
+
+/// All supported api providers. The first one is used by default.
+pub const PROVIDERS: [&Provider; 3] = [&coinmarketcap::PROVIDER, &cryptocompare::PROVIDER, &custom::PROVIDER];
+
+/// Names of all providers, e.g. as possible values of args
+pub fn provider_names() -> Vec<&'static str> {
+    PROVIDERS.iter().map(|p| p.name).collect()
+}
+
+pub fn default_provider() -> &'static Provider {
+    PROVIDERS[0]
+}
+
+pub fn provider_by_name(name: &str) -> AppResult<&'static Provider> {
+    PROVIDERS
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| AppError::Provider { name: name.into() })
+}
+
+/// Description of all providers, e.g. for help of args
+pub fn providers_help() -> String {
+    let providers: Vec<String> = PROVIDERS
+        .iter()
+        .map(|p| match p.env_keys {
+            [] => format!("{} ({})", p.name, p.description),
+            keys => format!("{} ({}, needs {})", p.name, p.description, keys.join(", ")),
+        })
+        .collect();
+    providers.join("; ")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::api::custom::CustomSettings;
+
+    #[test]
+    fn register_providers() {
+        assert_eq!(provider_names(), vec!["coinmarketcap", "cryptocompare", "custom"]);
+        assert_eq!(default_provider().name, "coinmarketcap");
+        let config_file = ConfigFile {
+            custom: Some(CustomSettings {
+                url: "https://example.com/{symbols}?convert={fiat}".into(),
+                headers: Default::default(),
+                price: "$.{symbol}.{fiat}".into(),
+                percent_change_24h: None,
+                market_cap: None,
+            }),
+            ..ConfigFile::default()
+        };
+        for provider in PROVIDERS.iter() {
+            let api = provider.create(None, &config_file).unwrap();
+            assert_eq!(api.get_name(), provider.name);
+            assert_eq!(api.capabilities().key_env, provider.env_keys.first().cloned());
+        }
+        let custom = provider_by_name("custom").unwrap().create(None, &ConfigFile::default());
+        assert_eq!(custom.err().map(|e| e.kind()), Some("custom_provider"));
+        let endpoint = "http://localhost:3000";
+        let api = provider_by_name("cryptocompare").unwrap().create(Some(endpoint), &config_file).unwrap();
+        assert_eq!(api.get_endpoint(), endpoint);
+        assert_eq!(provider_by_name("xyz").err().map(|e| e.kind()), Some("provider"));
+        let help = providers_help();
+        assert!(help.starts_with("coinmarketcap (CoinMarketCap, quotes in all fiats, needs COINMARKETCAP_KEY); "));
+    }
+}
@@ -1,21 +1,208 @@
-use super::api::{Api};
+use super::api::{cache::CacheSettings, custom::CustomSettings, http::HttpSettings, Api};
+use super::{
+    errors::AppError,
+    format::{DisplayMode, Locale},
+    history::{History, HistorySettings},
+    keys::KeyMap,
+    theme::{Palettes, Theme},
+    types::{AppResult, Coins},
+};
+use chrono::Local;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
 
 type ApiBox = Box<dyn Api>;
 
 pub struct Config<'a> {
-    pub fiat_symbol: &'a str,
+    pub fiat_symbols: Vec<&'a str>,
     pub crypto_symbols: Vec<&'a str>,
-    pub is_development: bool,
-    pub api: ApiBox
+    pub api: ApiBox,
+    pub refresh_interval: Duration,
+    pub theme: Theme,
+    pub keymap: KeyMap,
+    pub history: Option<History>,
+    pub locale: Locale,
+    pub display_mode: DisplayMode,
 }
 
 impl<'a> Config<'a> {
-    pub fn new(crypto_symbols: Vec<&'a str>, fiat_symbol: &'a str, is_development: bool, api: ApiBox) -> Self {
+    pub fn new(
+        crypto_symbols: Vec<&'a str>,
+        fiat_symbols: Vec<&'a str>,
+        api: ApiBox,
+        refresh_interval: Duration,
+        theme: Theme,
+        keymap: KeyMap,
+    ) -> Self {
         Config {
             crypto_symbols,
-            fiat_symbol,
-            is_development,
-            api
+            fiat_symbols,
+            api,
+            refresh_interval,
+            theme,
+            keymap,
+            history: None,
+            locale: Locale::default(),
+            display_mode: DisplayMode::default(),
         }
     }
+
+    /// Stores all fetched coins in given history
+    pub fn with_history(mut self, history: History) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Formats numbers and prices using given locale
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Shows values as formatted by the api provider, if `display_mode` is `Provider`
+    pub fn with_display_mode(mut self, display_mode: DisplayMode) -> Self {
+        self.display_mode = display_mode;
+        self
+    }
+
+    /// First fiat, which is used e.g. to sort coins or show their history
+    pub fn primary_fiat(&self) -> &'a str {
+        self.fiat_symbols[0]
+    }
+
+    /// Fetches coins of all symbols from api provider and stores them in history, if there is any
+    pub fn fetch_coins(&self) -> AppResult<Coins> {
+        let coins = self.api.get_coin_details(&self.crypto_symbols, &self.fiat_symbols)?;
+        if let Some(history) = &self.history {
+            // a failing history shouldn't hide fetched coins
+            let time = coins.cached_at.unwrap_or_else(Local::now);
+            if let Err(e) = history.record(&coins, self.api.get_name(), &time) {
+                warn!("failed to store history {}", e);
+            }
+        }
+        Ok(coins)
+    }
+}
+
+/// Settings stored in a config file (`~/.config/wtch-crpts/config.toml` by default)
+#[derive(Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ConfigFile {
+    /// Name of api provider, e.g. `cryptocompare`
+    pub provider: Option<String>,
+    pub theme: Option<String>,
+    pub themes: Palettes,
+    pub keymap: Option<String>,
+    /// Custom key bindings as action name -> key names
+    pub keys: HashMap<String, Vec<String>>,
+    /// Locale to format numbers, e.g. `de`
+    pub locale: Option<String>,
+    /// Mode to display values, e.g. `provider`
+    pub display: Option<String>,
+    pub history: HistorySettings,
+    pub cache: CacheSettings,
+    pub http: HttpSettings,
+    /// Settings of provider `custom`
+    pub custom: Option<CustomSettings>,
+}
+
+impl ConfigFile {
+    /// Loads a config file from given path. Without a path it loads the default config file, if there is any.
+    pub fn load(path: Option<&str>) -> AppResult<ConfigFile> {
+        let path = match path {
+            Some(p) => PathBuf::from(p),
+            None => match ConfigFile::default_path() {
+                Some(p) if p.exists() => p,
+                _ => return Ok(ConfigFile::default()),
+            },
+        };
+        let display_path = path.display().to_string();
+        let content = fs::read_to_string(&path).map_err(|cause| AppError::ConfigRead {
+            path: display_path.clone(),
+            cause,
+        })?;
+        info!("load config file {}", display_path);
+        ConfigFile::parse(&content).map_err(|cause| AppError::ConfigParse {
+            path: display_path,
+            cause,
+        })
+    }
+
+    fn parse(content: &str) -> Result<ConfigFile, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("wtch-crpts").join("config.toml"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_config_file() {
+        let content = r##"
+            provider = "cryptocompare"
+            theme = "custom"
+            locale = "de_CH"
+            display = "provider"
+
+            [themes.custom]
+            base = "light"
+            selected = "#00ff00"
+
+            [keys]
+            refresh = ["r", "f5"]
+
+            [history]
+            path = "history.db"
+            retention_days = 30
+
+            [cache]
+            ttl = 300
+
+            [http]
+            read_timeout = 20
+            retries = 0
+
+            [custom]
+            url = "https://example.com/{symbols}?convert={fiat}"
+            price = "$.{symbol}.{fiat}"
+
+            [custom.headers]
+            Authorization = "Bearer ${MY_KEY}"
+        "##;
+        let result = ConfigFile::parse(content).unwrap();
+        assert_eq!(result.provider, Some("cryptocompare".into()));
+        assert_eq!(result.theme, Some("custom".into()));
+        assert_eq!(result.locale, Some("de_CH".into()));
+        assert_eq!(result.display, Some("provider".into()));
+        let palette = result.themes.get("custom").unwrap();
+        assert_eq!(palette.base, Some("light".into()));
+        assert_eq!(palette.selected, Some("#00ff00".into()));
+        assert_eq!(palette.text, None);
+        assert_eq!(result.keys.get("refresh"), Some(&vec!["r".to_string(), "f5".to_string()]));
+        assert_eq!(result.history.path, Some("history.db".into()));
+        assert_eq!(result.history.retention_days, Some(30));
+        assert_eq!(result.history.downsample_minutes, None);
+        assert_eq!(result.cache.ttl, Some(300));
+        assert_eq!(result.http.read_timeout, Some(20));
+        assert_eq!(result.http.retries, Some(0));
+        assert_eq!(result.http.connect_timeout, None);
+        let custom = result.custom.unwrap();
+        assert_eq!(custom.url, "https://example.com/{symbols}?convert={fiat}");
+        assert_eq!(custom.price, "$.{symbol}.{fiat}");
+        assert_eq!(custom.market_cap, None);
+        assert_eq!(custom.headers.get("Authorization"), Some(&"Bearer ${MY_KEY}".to_string()));
+    }
+
+    #[test]
+    fn parse_empty_config_file() {
+        assert_eq!(ConfigFile::parse("").unwrap(), ConfigFile::default());
+    }
 }
@@ -9,8 +9,21 @@ pub const FIAT_LIST: [&str; 93] = [
     "LKR", "SEK", "CHF", "THB", "TTD", "TND", "TRY", "UGX", "UAH", "AED", "UYU", "UZS", "VND",
 ];
 
-// Api providers supporting public endpoints to get crypto data
-pub const API_PROVIDERS: [&str; 2] = [
-    "coinmarketcap", 
-    "cryptocompare"
+// Cryptocurrencies, which can be used as quote currency (e.g. ETH in BTC) besides `FIAT_LIST`
+pub const CRYPTO_LIST: [&str; 40] = [
+    "BTC", "ETH", "XRP", "LTC", "BCH", "EOS", "BNB", "USDT", "XLM", "TRX", "ADA", "XMR", "DASH", "ETC", "NEO", "XTZ",
+    "ZEC", "DOGE", "LINK", "ATOM", "DOT", "SOL", "AVAX", "MATIC", "UNI", "SHIB", "USDC", "DAI", "BSV", "XEM", "ONT",
+    "VET", "QTUM", "IOTA", "WAVES", "ALGO", "FIL", "TON", "NEAR", "OKB",
 ];
+
+// Default interval (in seconds) to refresh data of cryptocurrencies
+pub const DEFAULT_REFRESH_INTERVAL: &str = "60";
+
+// Duration (in milliseconds) to highlight a changed price after a refresh
+pub const PRICE_FLASH_DURATION: u64 = 1500;
+
+// Interval (in milliseconds) of ticks to update the UI
+pub const TICK_RATE: u64 = 250;
+
+// Max. duration (in milliseconds) between two clicks to handle them as a double click
+pub const DOUBLE_CLICK_DURATION: u64 = 400;
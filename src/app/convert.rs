@@ -0,0 +1,204 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{
+    api::Api,
+    constants::FIAT_LIST,
+    decimal::Decimal,
+    errors::AppError,
+    format::decimals,
+    types::{AppResult, Coin},
+};
+
+/// Rounds an amount to the decimals of its currency
+pub fn round(amount: &Decimal, currency: &str) -> Decimal {
+    amount.round(decimals(currency))
+}
+
+/// Amount to convert from one currency to another, e.g. `0.5 BTC EUR`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionQuery {
+    pub amount: Decimal,
+    pub from: String,
+    pub to: String,
+}
+
+impl FromStr for ConversionQuery {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || AppError::ConversionQuery { query: s.into() };
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts.as_slice() {
+            [amount, from, to] => Ok(ConversionQuery {
+                amount: amount.parse().map_err(|_| error())?,
+                from: from.to_uppercase(),
+                to: to.to_uppercase(),
+            }),
+            _ => Err(error()),
+        }
+    }
+}
+
+impl ConversionQuery {
+    /// Symbols and fiats to request to convert. Cryptocurrencies are converted through `common_fiat`,
+    /// fiats through BTC.
+    pub fn request<'a>(&'a self, common_fiat: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+        let (mut fiats, mut symbols): (Vec<&str>, Vec<&str>) =
+            [self.from.as_str(), self.to.as_str()].iter().partition(|c| FIAT_LIST.contains(c));
+        symbols.dedup();
+        fiats.dedup();
+        if symbols.is_empty() {
+            symbols.push("BTC");
+        }
+        if fiats.is_empty() {
+            fiats.push(common_fiat);
+        }
+        (symbols, fiats)
+    }
+}
+
+/// Converted amount
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion {
+    pub query: ConversionQuery,
+    /// Amount in currency `to`, rounded to its decimals
+    pub value: Decimal,
+    /// Currency the conversion is done through, if there is no direct rate
+    pub via: Option<String>,
+}
+
+impl fmt::Display for Conversion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} = {:.*} {}",
+            self.query.amount,
+            self.query.from,
+            decimals(&self.query.to),
+            self.value,
+            self.query.to
+        )?;
+        match &self.via {
+            Some(via) => write!(f, " (via {})", via),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Converts an amount using quotes of given coins. Cryptocurrencies without a direct rate are converted
+/// through a fiat both are quoted in, fiats through a cryptocurrency quoted in both.
+pub fn convert(query: &ConversionQuery, coins: &[Coin]) -> AppResult<Conversion> {
+    let price = |symbol: &str, fiat: &str| {
+        coins
+            .iter()
+            .find(|c| c.symbol == symbol)
+            .and_then(|c| c.price(fiat))
+            .filter(|p| !p.is_zero())
+    };
+    let (from, to) = (query.from.as_str(), query.to.as_str());
+    let amount = &query.amount;
+    // prices are never zero, so divisions don't fail
+    let div = |a: &Decimal, b: &Decimal| a.checked_div(b).unwrap_or_else(Decimal::zero);
+    let converted = |value: Decimal, via: Option<&str>| Conversion {
+        query: query.clone(),
+        value: round(&value, to),
+        via: via.map(String::from),
+    };
+    if from == to {
+        return Ok(converted(amount.clone(), None));
+    }
+    if let Some(p) = price(from, to) {
+        return Ok(converted(amount * p, None));
+    }
+    if let Some(p) = price(to, from) {
+        return Ok(converted(div(amount, p), None));
+    }
+    let fiats = coins.iter().find(|c| c.symbol == from).into_iter().flat_map(|c| c.quotes.keys());
+    for fiat in fiats {
+        if let (Some(p_from), Some(p_to)) = (price(from, fiat), price(to, fiat)) {
+            return Ok(converted(div(&(amount * p_from), p_to), Some(fiat)));
+        }
+    }
+    for coin in coins {
+        if let (Some(p_from), Some(p_to)) = (price(&coin.symbol, from), price(&coin.symbol, to)) {
+            return Ok(converted(div(&(amount * p_to), p_from), Some(&coin.symbol)));
+        }
+    }
+    Err(AppError::Conversion {
+        from: from.into(),
+        to: to.into(),
+    })
+}
+
+/// Requests current quotes from api provider and converts an amount
+pub fn fetch_conversion(api: &dyn Api, query: &ConversionQuery, common_fiat: &str) -> AppResult<Conversion> {
+    let (symbols, fiats) = query.request(common_fiat);
+    let coins = api.get_coin_details(&symbols, &fiats)?;
+    convert(query, &coins.list)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::types::CoinBuilder;
+
+    fn coins() -> Vec<Coin> {
+        vec![
+            CoinBuilder::default().symbol("BTC").price("EUR", 3200.0).price("JPY", 400_000.0).build().unwrap(),
+            CoinBuilder::default().symbol("ETH").price("EUR", 100.0).build().unwrap(),
+        ]
+    }
+
+    fn convert_str(query: &str) -> AppResult<Conversion> {
+        convert(&query.parse().unwrap(), &coins())
+    }
+
+    #[test]
+    fn convert_currencies() {
+        assert_eq!(convert_str("0.5 btc eur").unwrap().value, Decimal::from(1600));
+        assert_eq!(convert_str("100 EUR ETH").unwrap().value, Decimal::from(1));
+        let eth = convert_str("1 ETH BTC").unwrap();
+        assert_eq!(eth.value, Decimal::from(0.03125));
+        assert_eq!(eth.via.as_deref(), Some("EUR"));
+        let yen = convert_str("10 EUR JPY").unwrap();
+        assert_eq!(yen.value, Decimal::from(1250));
+        assert_eq!(yen.via.as_deref(), Some("BTC"));
+        assert!(convert_str("1 LTC EUR").is_err());
+        assert!("1 BTC".parse::<ConversionQuery>().is_err());
+        assert!("x BTC EUR".parse::<ConversionQuery>().is_err());
+    }
+
+    #[test]
+    fn round_per_currency() {
+        let round_str = |amount: &str, currency: &str| round(&amount.parse().unwrap(), currency).to_string();
+        assert_eq!(round_str("12.345678", "EUR"), "12.35");
+        assert_eq!(round_str("1234.5678", "JPY"), "1235");
+        assert_eq!(round_str("1.23456", "KWD"), "1.235");
+        assert_eq!(round_str("0.123456789", "BTC"), "0.12345679");
+        assert_eq!(convert_str("1 EUR BTC").unwrap().to_string(), "1 EUR = 0.00031250 BTC");
+        assert_eq!(convert_str("1 ETH BTC").unwrap().to_string(), "1 ETH = 0.03125000 BTC (via EUR)");
+    }
+
+    #[test]
+    fn request_symbols_and_fiats() {
+        let query: ConversionQuery = "1 ETH BTC".parse().unwrap();
+        assert_eq!(query.request("USD"), (vec!["ETH", "BTC"], vec!["USD"]));
+        let query: ConversionQuery = "1 EUR JPY".parse().unwrap();
+        assert_eq!(query.request("USD"), (vec!["BTC"], vec!["EUR", "JPY"]));
+        let query: ConversionQuery = "1 EUR BTC".parse().unwrap();
+        assert_eq!(query.request("USD"), (vec!["BTC"], vec!["EUR"]));
+    }
+    #[test]
+    fn convert_large_and_small_amounts() {
+        let coins = vec![
+            CoinBuilder::default().symbol("SHIB").price("USD", Decimal::from(0.000008123456)).build().unwrap(),
+            CoinBuilder::default().symbol("BTC").price("IRR", Decimal::from(2_812_345_678_901_i64)).build().unwrap(),
+        ];
+        let convert_str = |query: &str| convert(&query.parse().unwrap(), &coins).unwrap().to_string();
+        assert_eq!(convert_str("123456789012 SHIB USD"), "123456789012 SHIB = 1002895.79 USD");
+        assert_eq!(convert_str("1 USD SHIB"), "1 USD = 123100.31592465 SHIB");
+        assert_eq!(convert_str("9999999.99999999 BTC IRR"), "9999999.99999999 BTC = 28123456789009971876.54 IRR");
+    }
+}
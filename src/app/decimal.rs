@@ -0,0 +1,266 @@
+use bigdecimal::{BigDecimal, ParseBigDecimalError, Signed, Zero};
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+// Significant digits of a quotient, which doesn't terminate, e.g. `1 / 3`
+const DIVISION_PRECISION: u64 = 28;
+
+/// Exact decimal number of arbitrary precision, e.g. a price.
+/// Unlike `f32` it keeps all digits sent by api providers, e.g. of large prices in weak fiats
+/// or of sub-cent tokens like SHIB.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Decimal(BigDecimal);
+
+impl Decimal {
+    pub fn zero() -> Self {
+        Decimal(BigDecimal::zero())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0.is_positive()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.0.is_negative()
+    }
+
+    pub fn abs(&self) -> Decimal {
+        Decimal(self.0.abs())
+    }
+
+    /// Zeros between decimal mark and first significant digit, e.g. 4 of `0.00001234`
+    pub fn fraction_zeros(&self) -> usize {
+        let (_, scale) = self.0.as_bigint_and_exponent();
+        (scale - self.0.digits() as i64).max(0) as usize
+    }
+
+    /// Quotient rounded to `DIVISION_PRECISION` significant digits, or `None` if `other` is zero
+    pub fn checked_div(&self, other: &Decimal) -> Option<Decimal> {
+        if other.is_zero() {
+            return None;
+        }
+        let quotient = &self.0 / &other.0;
+        if quotient.digits() > DIVISION_PRECISION {
+            Some(Decimal(quotient.with_prec(DIVISION_PRECISION)))
+        } else {
+            Some(Decimal(quotient))
+        }
+    }
+
+    /// Rounds half away from zero to given number of decimals, e.g. `2.345` to `2.35`
+    pub fn round(&self, decimals: usize) -> Decimal {
+        let (_, scale) = self.0.as_bigint_and_exponent();
+        if scale <= decimals as i64 {
+            return self.clone();
+        }
+        let half: BigDecimal = format!("5e-{}", decimals + 1).parse().unwrap();
+        let rounded = if self.is_negative() {
+            &self.0 - &half
+        } else {
+            &self.0 + &half
+        };
+        // digits beyond `decimals` are cut off towards zero
+        Decimal(rounded.with_scale(decimals as i64))
+    }
+
+    /// Decimal of the shortest representation of a float, or `None` if it's not finite
+    pub fn from_f64(value: f64) -> Option<Decimal> {
+        if value.is_finite() {
+            value.to_string().parse().ok()
+        } else {
+            None
+        }
+    }
+
+    /// Closest `f64`, e.g. to store it in history or to draw a chart
+    pub fn to_f64(&self) -> f64 {
+        self.0.to_string().parse().unwrap_or_default()
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ParseBigDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BigDecimal::from_str(s.trim()).map(Decimal)
+    }
+}
+
+impl From<i64> for Decimal {
+    fn from(value: i64) -> Self {
+        Decimal(BigDecimal::from(value))
+    }
+}
+
+/// Floats are converted using their shortest representation, e.g. `3200.98` instead of `3200.97998046875`.
+/// Code uses decimals throughout, so it's just a shortcut to write values in tests.
+#[cfg(test)]
+impl From<f64> for Decimal {
+    fn from(value: f64) -> Self {
+        Decimal::from_f64(value).unwrap()
+    }
+}
+
+/// Written without exponent and rounded, if a precision is given, e.g. `{:.2}`
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => fmt::Display::fmt(&self.round(precision).0, f),
+            None => fmt::Display::fmt(&self.0, f),
+        }
+    }
+}
+
+impl fmt::Debug for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<'a> Add<&'a Decimal> for &'a Decimal {
+    type Output = Decimal;
+
+    fn add(self, other: &Decimal) -> Decimal {
+        Decimal(&self.0 + &other.0)
+    }
+}
+
+impl<'a> Sub<&'a Decimal> for &'a Decimal {
+    type Output = Decimal;
+
+    fn sub(self, other: &Decimal) -> Decimal {
+        Decimal(&self.0 - &other.0)
+    }
+}
+
+impl<'a> Mul<&'a Decimal> for &'a Decimal {
+    type Output = Decimal;
+
+    fn mul(self, other: &Decimal) -> Decimal {
+        Decimal(&self.0 * &other.0)
+    }
+}
+
+/// Serialized as json number with all its digits, which needs feature `arbitrary_precision` of serde_json
+impl Serialize for Decimal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let number: serde_json::Number =
+            serde_json::from_str(&self.0.to_string()).map_err(<S::Error as ser::Error>::custom)?;
+        number.serialize(serializer)
+    }
+}
+
+struct DecimalVisitor;
+
+impl<'de> Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a number or a decimal string")
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Decimal, E> {
+        value.parse().map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Decimal, E> {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Decimal, E> {
+        Ok(Decimal(BigDecimal::from(value)))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Decimal, E> {
+        // shortest representation, since the digits of the number are lost already
+        self.visit_str(&value.to_string())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Decimal, A::Error> {
+        // serde_json passes numbers of feature `arbitrary_precision` as single entry with their digits
+        match map.next_entry::<String, String>()? {
+            Some((_, digits)) => self.visit_str(&digits),
+            None => Err(de::Error::invalid_length(0, &self)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DecimalVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn decimal(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn deserialize_exact() {
+        let large: Decimal = serde_json::from_str("1234567890123456789.123456789").unwrap();
+        assert_eq!(large.to_string(), "1234567890123456789.123456789");
+        let small: Decimal = serde_json::from_str("0.000000012345678901234567").unwrap();
+        assert_eq!(small.to_string(), "0.000000012345678901234567");
+        let exponent: Decimal = serde_json::from_str("1.5e-9").unwrap();
+        assert_eq!(exponent.to_string(), "0.0000000015");
+        assert_eq!(serde_json::from_str::<Decimal>("\"37.25\"").unwrap(), decimal("37.25"));
+        assert_eq!(serde_json::from_str::<Decimal>("-42").unwrap(), Decimal::from(-42));
+        assert!(serde_json::from_str::<Decimal>("\"abc\"").is_err());
+    }
+
+    #[test]
+    fn serialize_exact() {
+        let value = decimal("98765432109876543210.0000000001");
+        assert_eq!(serde_json::to_string(&value).unwrap(), "98765432109876543210.0000000001");
+        let json = serde_json::to_string(&Some(decimal("0.00000000000001"))).unwrap();
+        assert_eq!(serde_json::from_str::<Option<Decimal>>(&json).unwrap(), Some(decimal("0.00000000000001")));
+    }
+
+    #[test]
+    fn format_rounded() {
+        assert_eq!(format!("{:.2}", decimal("2.345")), "2.35");
+        assert_eq!(format!("{:.2}", decimal("-2.345")), "-2.35");
+        assert_eq!(format!("{:+.2}", decimal("0.5")), "+0.50");
+        assert_eq!(format!("{:.0}", decimal("56110256905.5")), "56110256906");
+        assert_eq!(format!("{:.8}", decimal("0.000000011")), "0.00000001");
+        assert_eq!(format!("{:>6}", decimal("1.5")), "   1.5");
+        assert_eq!(decimal("1.005").round(2), decimal("1.01"));
+        assert_eq!(decimal("1.5").round(3), decimal("1.5"));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let price = decimal("0.00001234");
+        assert_eq!(&price * &Decimal::from(1_000_000), decimal("12.34"));
+        assert_eq!(&price + &decimal("0.00000001"), decimal("0.00001235"));
+        assert_eq!(&price - &price, Decimal::zero());
+        assert_eq!(decimal("1").checked_div(&decimal("8")), Some(decimal("0.125")));
+        assert_eq!(decimal("1").checked_div(&decimal("3")).unwrap().to_string(), format!("0.{}", "3".repeat(28)));
+        assert_eq!(decimal("1").checked_div(&Decimal::zero()), None);
+        assert!(decimal("0.1") < decimal("0.11"));
+        assert_eq!(decimal("3200.98").to_f64(), 3200.98);
+        assert_eq!(decimal("-1.5").abs(), decimal("1.5"));
+    }
+
+    #[test]
+    fn fraction_zeros_and_floats() {
+        assert_eq!(decimal("0.00001234").fraction_zeros(), 4);
+        assert_eq!(decimal("0.0100").fraction_zeros(), 1);
+        assert_eq!(decimal("0.5").fraction_zeros(), 0);
+        assert_eq!(decimal("12.5").fraction_zeros(), 0);
+        assert_eq!(Decimal::from_f64(-0.8), Some(decimal("-0.8")));
+        assert_eq!(Decimal::from_f64(f64::NAN), None);
+    }
+}
@@ -1,14 +1,14 @@
 use dotenv::dotenv;
 use std::env;
-use std::sync::{Once, ONCE_INIT};
+use std::sync::Once;
 
 use super::errors;
 
-static INIT_ENV: Once = ONCE_INIT;
+static INIT_ENV: Once = Once::new();
 
 pub fn get_env(key: &str) -> Result<String, errors::AppError> {
     INIT_ENV.call_once(|| {
         dotenv().ok();
     });
-    env::var(key.to_string()).map_err(|_| errors::AppError::Env { name: key.into() })
+    env::var(key).map_err(|_| errors::AppError::Env { name: key.into() })
 }
@@ -1,3 +1,6 @@
+// impls generated by `failure` are nested in constants
+#![allow(non_local_definitions)]
+
 #[derive(Debug, Fail)]
 pub enum AppError {
     #[fail(display = "Failed to get envoirenment variable {}", name)]
@@ -6,12 +9,136 @@ pub enum AppError {
     SerdeError(#[cause] serde_json::Error),
     #[fail(display = "Request to Api failed {:?}", _0)]
     ApiRequest(#[cause] reqwest::Error),
+    #[fail(display = "Request to {} timed out", url)]
+    ApiTimeout { url: String },
+    #[fail(display = "Request to {} was rejected with status {}: {}", url, status, body)]
+    ApiRejected { url: String, status: u16, body: String },
     #[fail(display = "Parsing url failed {:?}", _0)]
     ApiParseUrl(#[cause] reqwest::UrlError),
     #[fail(display = "Failed to parse value of {} from list", key)]
     ApiParseMap { key: String },
+    #[fail(display = "Api provider {} is not supported", name)]
+    Provider { name: String },
+    #[fail(display = "Provider {} doesn't support fiat {}", provider, fiat)]
+    UnsupportedFiat { provider: String, fiat: String },
+    #[fail(display = "Provider {} needs an api key in environment variable {}", provider, name)]
+    ApiKey { provider: String, name: String },
+    #[fail(display = "Custom provider is not configured properly: {}", reason)]
+    CustomProvider { reason: String },
+    #[fail(display = "Invalid json path {}: {}", path, reason)]
+    JsonPath { path: String, reason: String },
     #[fail(display = "Terminal IO error {:?}", _0)]
     Terminal(#[cause] std::io::Error),
+    #[fail(display = "Failed to read config file {}", path)]
+    ConfigRead {
+        path: String,
+        #[cause]
+        cause: std::io::Error,
+    },
+    #[fail(display = "Failed to parse config file {}: {}", path, cause)]
+    ConfigParse {
+        path: String,
+        #[cause]
+        cause: toml::de::Error,
+    },
+    #[fail(display = "Theme {} is not defined", name)]
+    Theme { name: String },
+    #[fail(display = "Invalid theme color {}", color)]
+    ThemeColor { color: String },
+    #[fail(display = "Key map {} is not defined", name)]
+    KeyMap { name: String },
+    #[fail(display = "Unknown action {} to bind keys to", name)]
+    KeyAction { name: String },
+    #[fail(display = "Invalid key {}", key)]
+    KeyBinding { key: String },
+    #[fail(display = "Output format {} is not supported", name)]
+    OutputFormat { name: String },
+    #[fail(display = "Locale {} is not supported", name)]
+    Locale { name: String },
+    #[fail(display = "Display mode {} is not supported", name)]
+    DisplayMode { name: String },
+    #[fail(display = "Failed to write output {:?}", _0)]
+    Output(#[cause] std::io::Error),
+    #[fail(display = "Failed to write csv {:?}", _0)]
+    Csv(#[cause] csv::Error),
+    #[fail(display = "Invalid template: {}", reason)]
+    Template { reason: String },
+    #[fail(display = "Failed to handle signals {:?}", _0)]
+    Signal(#[cause] ctrlc::Error),
+    #[fail(display = "Failed to start server at {}: {}", addr, reason)]
+    Server { addr: String, reason: String },
+    #[fail(display = "History database error {}", _0)]
+    History(#[cause] rusqlite::Error),
+    #[fail(display = "Failed to open history {}", path)]
+    HistoryOpen {
+        path: String,
+        #[cause]
+        cause: std::io::Error,
+    },
+    #[fail(display = "Failed to write cache {}", path)]
+    Cache {
+        path: String,
+        #[cause]
+        cause: std::io::Error,
+    },
+    #[fail(display = "Failed to read or write fixture {}", path)]
+    Fixture {
+        path: String,
+        #[cause]
+        cause: std::io::Error,
+    },
+    #[fail(display = "Failure {} is not supported", name)]
+    Failure { name: String },
+    #[fail(display = "Invalid date or time {}, expected e.g. 2019-02-09 or 2019-02-09T17:34:23+01:00", value)]
+    DateTime { value: String },
+    #[fail(display = "Invalid conversion {}, expected e.g. 0.5 BTC EUR", query)]
+    ConversionQuery { query: String },
+    #[fail(display = "No rate to convert {} to {}", from, to)]
+    Conversion { from: String, to: String },
+}
+
+impl AppError {
+    /// Name of an error variant, e.g. to use it as a label of metrics
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Env { .. } => "env",
+            AppError::SerdeError(_) => "serde",
+            AppError::ApiRequest(_) => "api_request",
+            AppError::ApiTimeout { .. } => "api_timeout",
+            AppError::ApiRejected { .. } => "api_rejected",
+            AppError::ApiParseUrl(_) => "api_parse_url",
+            AppError::ApiParseMap { .. } => "api_parse_map",
+            AppError::Provider { .. } => "provider",
+            AppError::UnsupportedFiat { .. } => "unsupported_fiat",
+            AppError::ApiKey { .. } => "api_key",
+            AppError::CustomProvider { .. } => "custom_provider",
+            AppError::JsonPath { .. } => "json_path",
+            AppError::Terminal(_) => "terminal",
+            AppError::ConfigRead { .. } => "config_read",
+            AppError::ConfigParse { .. } => "config_parse",
+            AppError::Theme { .. } => "theme",
+            AppError::ThemeColor { .. } => "theme_color",
+            AppError::KeyMap { .. } => "key_map",
+            AppError::KeyAction { .. } => "key_action",
+            AppError::KeyBinding { .. } => "key_binding",
+            AppError::OutputFormat { .. } => "output_format",
+            AppError::Locale { .. } => "locale",
+            AppError::DisplayMode { .. } => "display_mode",
+            AppError::Output(_) => "output",
+            AppError::Csv(_) => "csv",
+            AppError::Template { .. } => "template",
+            AppError::Signal(_) => "signal",
+            AppError::Server { .. } => "server",
+            AppError::History(_) => "history",
+            AppError::HistoryOpen { .. } => "history_open",
+            AppError::Cache { .. } => "cache",
+            AppError::Fixture { .. } => "fixture",
+            AppError::Failure { .. } => "failure",
+            AppError::DateTime { .. } => "date_time",
+            AppError::ConversionQuery { .. } => "conversion_query",
+            AppError::Conversion { .. } => "conversion",
+        }
+    }
 }
 
 impl From<serde_json::Error> for AppError {
@@ -25,3 +152,9 @@ impl From<reqwest::Error> for AppError {
         AppError::ApiRequest(e)
     }
 }
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        AppError::History(e)
+    }
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use super::{
+    errors::AppError,
+    history::{History, HistoryFilter, HistoryRecord},
+    types::AppResult,
+};
+
+// Formats supported to export and import history
+pub const EXPORT_FORMATS: [&str; 2] = ["csv", "jsonl"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+}
+
+impl FromStr for ExportFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" => Ok(ExportFormat::JsonLines),
+            _ => Err(AppError::OutputFormat { name: s.into() }),
+        }
+    }
+}
+
+/// Parses a time (e.g. `2019-02-09T17:34:23+01:00`) or a local date (e.g. `2019-02-09`).
+/// A date is the start of the day, or the start of the next day if `end_of_day` is set,
+/// so a range of dates includes its last day.
+pub fn parse_time(value: &str, end_of_day: bool) -> AppResult<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Local));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| AppError::DateTime { value: value.into() })?;
+    let date = if end_of_day { date.succ_opt() } else { Some(date) };
+    date.and_then(|d| d.and_hms_opt(0, 0, 0))
+        .and_then(|d| Local.from_local_datetime(&d).earliest())
+        .ok_or_else(|| AppError::DateTime { value: value.into() })
+}
+
+/// Writes stored quotes selected by filter. Returns number of written quotes.
+pub fn export_history<W: Write>(
+    history: &History,
+    filter: &HistoryFilter,
+    format: ExportFormat,
+    mut out: W,
+) -> AppResult<usize> {
+    let records = history.records(filter)?;
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for record in &records {
+                writer.serialize(record).map_err(AppError::Csv)?;
+            }
+            writer.flush().map_err(AppError::Output)?;
+        }
+        ExportFormat::JsonLines => {
+            for record in &records {
+                serde_json::to_writer(&mut out, record)?;
+                writeln!(out).map_err(AppError::Output)?;
+            }
+            out.flush().map_err(AppError::Output)?;
+        }
+    }
+    Ok(records.len())
+}
+
+/// Reads quotes, which were exported before, and stores them. Returns number of stored quotes.
+pub fn import_history<R: Read>(history: &History, format: ExportFormat, input: R) -> AppResult<usize> {
+    let records: Vec<HistoryRecord> = match format {
+        ExportFormat::Csv => csv::Reader::from_reader(input)
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .map_err(AppError::Csv)?,
+        ExportFormat::JsonLines => serde_json::Deserializer::from_reader(input)
+            .into_iter()
+            .collect::<Result<_, _>>()?,
+    };
+    history.import(records)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::decimal::Decimal;
+    use crate::app::history::Retention;
+    use crate::app::types::{CoinBuilder, Coins, QuoteBuilder};
+
+    fn history() -> History {
+        let history = History::open(":memory:", Retention::default()).unwrap();
+        let quote = QuoteBuilder::default()
+            .price("3200.123456789012345678".parse::<Decimal>().unwrap())
+            .percent_change_24h(Decimal::from(-0.5))
+            .build()
+            .unwrap();
+        let coins = Coins::new(vec![
+            CoinBuilder::default().symbol("BTC").quote("EUR", quote).build().unwrap(),
+            CoinBuilder::default().symbol("LTC").price("EUR", 37.25).build().unwrap(),
+        ]);
+        let time = Local.timestamp_opt(1_549_733_663, 0).unwrap();
+        history.record(&coins, "coinmarketcap", &time).unwrap();
+        history
+    }
+
+    fn export(format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        export_history(&history(), &HistoryFilter::default(), format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn export_and_import() {
+        for format in &[ExportFormat::Csv, ExportFormat::JsonLines] {
+            let exported = export(*format);
+            let imported = History::open(":memory:", Retention::default()).unwrap();
+            assert_eq!(import_history(&imported, *format, exported.as_bytes()).unwrap(), 2);
+            assert_eq!(
+                imported.records(&HistoryFilter::default()).unwrap(),
+                history().records(&HistoryFilter::default()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn export_csv() {
+        let exported = export(ExportFormat::Csv);
+        let mut lines = exported.lines();
+        assert_eq!(
+            lines.next(),
+            Some("symbol,fiat,provider,timestamp,price,percent_change_24h,market_cap")
+        );
+        assert!(lines.next().unwrap().starts_with("BTC,EUR,coinmarketcap,2019-02-09T"));
+        assert!(lines.next().unwrap().ends_with(",37.25,,"));
+    }
+
+    #[test]
+    fn parse_times() {
+        let date = parse_time("2019-02-09", false).unwrap();
+        assert_eq!(date.format("%Y-%m-%d %H:%M").to_string(), "2019-02-09 00:00");
+        let date = parse_time("2019-02-09", true).unwrap();
+        assert_eq!(date.format("%Y-%m-%d %H:%M").to_string(), "2019-02-10 00:00");
+        let time = parse_time("2019-02-09T17:34:23Z", true).unwrap();
+        assert_eq!(time.timestamp(), 1_549_733_663);
+        assert!(parse_time("09.02.2019", false).is_err());
+    }
+}
@@ -0,0 +1,408 @@
+use std::str::FromStr;
+
+use super::{decimal::Decimal, env::get_env, errors::AppError, types::AppResult};
+
+// Names of built-in locales
+pub const LOCALES: [&str; 10] = ["en", "de", "de_CH", "es", "fr", "it", "ja", "nl", "pt", "ru"];
+
+// Names of modes to display values
+pub const DISPLAY_MODES: [&str; 2] = ["local", "provider"];
+
+// Environment variables to detect the locale, in order of precedence
+const LOCALE_ENVS: [&str; 3] = ["LC_ALL", "LC_NUMERIC", "LANG"];
+
+// Decimals of cryptocurrencies, e.g. satoshis of BTC
+const CRYPTO_DECIMALS: usize = 8;
+// Significant digits shown of prices below 1, e.g. `0.00001234`
+const PRICE_SIGNIFICANT_DIGITS: usize = 4;
+// Max. decimals shown of a price
+const MAX_PRICE_DECIMALS: usize = 18;
+// Suffixes of compact numbers, e.g. `1.2B`
+const COMPACT_UNITS: [(&str, i64); 4] = [
+    ("T", 1_000_000_000_000),
+    ("B", 1_000_000_000),
+    ("M", 1_000_000),
+    ("K", 1_000),
+];
+
+// Symbol and ISO 4217 minor units of all fiats of `FIAT_LIST`
+const CURRENCIES: [(&str, &str, usize); 93] = [
+    ("USD", "$", 2),
+    ("ALL", "L", 2),
+    ("DZD", "د.ج", 2),
+    ("ARS", "$", 2),
+    ("AMD", "֏", 2),
+    ("AUD", "A$", 2),
+    ("AZN", "₼", 2),
+    ("BHD", ".د.ب", 3),
+    ("BDT", "৳", 2),
+    ("BYN", "Br", 2),
+    ("BMD", "$", 2),
+    ("BOB", "Bs.", 2),
+    ("BAM", "KM", 2),
+    ("BRL", "R$", 2),
+    ("BGN", "лв", 2),
+    ("KHR", "៛", 2),
+    ("CAD", "CA$", 2),
+    ("CLP", "$", 0),
+    ("CNY", "¥", 2),
+    ("COP", "$", 2),
+    ("CRC", "₡", 2),
+    ("HRK", "kn", 2),
+    ("CUP", "$", 2),
+    ("CZK", "Kč", 2),
+    ("DKK", "kr", 2),
+    ("DOP", "RD$", 2),
+    ("EGP", "E£", 2),
+    ("EUR", "€", 2),
+    ("GEL", "₾", 2),
+    ("GHS", "₵", 2),
+    ("GTQ", "Q", 2),
+    ("HNL", "L", 2),
+    ("HKD", "HK$", 2),
+    ("HUF", "Ft", 2),
+    ("ISK", "kr", 0),
+    ("INR", "₹", 2),
+    ("IDR", "Rp", 2),
+    ("IRR", "﷼", 2),
+    ("IQD", "ع.د", 3),
+    ("ILS", "₪", 2),
+    ("JMD", "J$", 2),
+    ("JPY", "¥", 0),
+    ("JOD", "د.ا", 3),
+    ("KZT", "₸", 2),
+    ("KES", "KSh", 2),
+    ("KWD", "د.ك", 3),
+    ("KGS", "с", 2),
+    ("LBP", "ل.ل", 2),
+    ("MKD", "ден", 2),
+    ("MYR", "RM", 2),
+    ("MUR", "₨", 2),
+    ("MXN", "MX$", 2),
+    ("MDL", "L", 2),
+    ("MNT", "₮", 2),
+    ("MAD", "د.م.", 2),
+    ("MMK", "K", 2),
+    ("NAD", "N$", 2),
+    ("NPR", "₨", 2),
+    ("TWD", "NT$", 2),
+    // listed as `NDZ` by coinmarketcap, which is the New Zealand dollar
+    ("NDZ", "NZ$", 2),
+    ("NIO", "C$", 2),
+    ("NGN", "₦", 2),
+    ("NOK", "kr", 2),
+    ("OMR", "ر.ع.", 3),
+    ("PKR", "₨", 2),
+    ("PAB", "B/.", 2),
+    ("PEN", "S/", 2),
+    ("PHP", "₱", 2),
+    ("PLN", "zł", 2),
+    ("GBP", "£", 2),
+    ("QAR", "ر.ق", 2),
+    ("RON", "lei", 2),
+    ("RUB", "₽", 2),
+    ("SAR", "ر.س", 2),
+    ("RSD", "дин.", 2),
+    ("SGD", "S$", 2),
+    ("ZAR", "R", 2),
+    ("KRW", "₩", 0),
+    ("SSP", "£", 2),
+    ("VES", "Bs.S", 2),
+    ("LKR", "Rs", 2),
+    ("SEK", "kr", 2),
+    ("CHF", "CHF", 2),
+    ("THB", "฿", 2),
+    ("TTD", "TT$", 2),
+    ("TND", "د.ت", 3),
+    ("TRY", "₺", 2),
+    ("UGX", "USh", 0),
+    ("UAH", "₴", 2),
+    ("AED", "د.إ", 2),
+    ("UYU", "$U", 2),
+    ("UZS", "soʻm", 2),
+    ("VND", "₫", 0),
+];
+
+/// Symbol of a fiat, e.g. `€` of EUR
+pub fn currency_symbol(currency: &str) -> Option<&'static str> {
+    CURRENCIES.iter().find(|c| c.0 == currency).map(|c| c.1)
+}
+
+/// Decimals of the minor unit of a fiat, e.g. 2 of EUR (cents) or 0 of JPY
+pub fn minor_units(currency: &str) -> Option<usize> {
+    CURRENCIES.iter().find(|c| c.0 == currency).map(|c| c.2)
+}
+
+/// Number of decimals an amount of a currency is rounded to
+pub fn decimals(currency: &str) -> usize {
+    minor_units(currency).unwrap_or(CRYPTO_DECIMALS)
+}
+
+/// Conventions to write numbers and amounts of money, e.g. `1.234,56 €`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Locale {
+    group_separator: char,
+    decimal_mark: char,
+    /// Currency symbol is written before the amount, e.g. `$1.5`
+    symbol_first: bool,
+    /// Currency symbol is separated from the amount by a space, e.g. `1,5 €`
+    symbol_space: bool,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::new(',', '.', true, false)
+    }
+}
+
+impl Locale {
+    fn new(group_separator: char, decimal_mark: char, symbol_first: bool, symbol_space: bool) -> Self {
+        Locale {
+            group_separator,
+            decimal_mark,
+            symbol_first,
+            symbol_space,
+        }
+    }
+
+    fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "en" => Some(Locale::default()),
+            "de" | "es" | "it" => Some(Locale::new('.', ',', false, true)),
+            "de_CH" => Some(Locale::new('’', '.', true, true)),
+            "fr" => Some(Locale::new('\u{202f}', ',', false, true)),
+            "ja" => Some(Locale::new(',', '.', true, false)),
+            "nl" | "pt" => Some(Locale::new('.', ',', true, true)),
+            "ru" => Some(Locale::new('\u{a0}', ',', false, true)),
+            _ => None,
+        }
+    }
+
+    /// Built-in locale by name, e.g. `de`. Names of system locales (e.g. `de_DE.UTF-8`)
+    /// fall back to their language, if there is no locale of their region.
+    pub fn by_name(name: &str) -> AppResult<Self> {
+        let region = name.split('.').next().unwrap_or(name).replace('-', "_");
+        let language = region.split('_').next().unwrap_or("");
+        Locale::built_in(&region)
+            .or_else(|| Locale::built_in(language))
+            .ok_or_else(|| AppError::Locale { name: name.into() })
+    }
+
+    /// Locale of environment variables (e.g. `LANG`), if it's a built-in one
+    pub fn from_env() -> Option<Self> {
+        LOCALE_ENVS
+            .iter()
+            .filter_map(|name| get_env(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::by_name(&value).ok())
+    }
+
+    /// Number rounded to given decimals with separated thousands, e.g. `1,234.50`
+    pub fn number(&self, value: &Decimal, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value);
+        let (sign, digits) = match formatted.chars().next() {
+            Some('-') => ("-", &formatted[1..]),
+            _ => ("", formatted.as_str()),
+        };
+        let mut parts = digits.splitn(2, '.');
+        let integer = parts.next().unwrap_or("");
+        let mut result = String::from(sign);
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                result.push(self.group_separator);
+            }
+            result.push(digit);
+        }
+        if let Some(fraction) = parts.next() {
+            result.push(self.decimal_mark);
+            result.push_str(fraction);
+        }
+        result
+    }
+
+    /// Price with currency symbol, e.g. `$3,200.98`. Prices below 1 keep their first significant digits,
+    /// e.g. `$0.00001234`. Prices too small to be shown by `MAX_PRICE_DECIMALS` are shown as a bound,
+    /// e.g. `<$0.000000000000000001`.
+    pub fn price(&self, value: &Decimal, currency: &str) -> String {
+        let decimals = if !value.is_zero() && value.abs() < Decimal::from(1) {
+            (value.fraction_zeros() + PRICE_SIGNIFICANT_DIGITS)
+                .max(decimals(currency))
+                .min(MAX_PRICE_DECIMALS)
+        } else {
+            decimals(currency)
+        };
+        if !value.is_zero() && value.round(decimals).is_zero() {
+            let smallest = format!("0{}{}1", self.decimal_mark, "0".repeat(decimals - 1));
+            let (bound, sign) = if value.is_negative() { (">", "-") } else { ("<", "") };
+            return format!("{}{}", bound, self.money(format!("{}{}", sign, smallest), currency));
+        }
+        self.money(self.number(value, decimals), currency)
+    }
+
+    /// Change in percent with sign, e.g. `+1.23%`
+    pub fn change(&self, value: &Decimal) -> String {
+        let sign = if value.is_positive() { "+" } else { "" };
+        format!("{}{}%", sign, self.number(value, 2))
+    }
+
+    /// Large amount (e.g. market cap) in short form with currency symbol, e.g. `$1.2B`
+    pub fn compact(&self, value: &Decimal, currency: &str) -> String {
+        let abs = value.abs();
+        let amount = COMPACT_UNITS
+            .iter()
+            .map(|(suffix, unit)| (suffix, Decimal::from(*unit)))
+            .find(|(_, unit)| abs >= *unit)
+            .and_then(|(suffix, unit)| value.checked_div(&unit).map(|v| format!("{}{}", self.number(&v, 1), suffix)))
+            .unwrap_or_else(|| self.number(value, 0));
+        self.money(amount, currency)
+    }
+
+    /// Adds the symbol of a fiat or the code of a cryptocurrency to an amount
+    fn money(&self, amount: String, currency: &str) -> String {
+        let space = if self.symbol_space { " " } else { "" };
+        match currency_symbol(currency) {
+            Some(symbol) if self.symbol_first => match amount.chars().next() {
+                Some('-') => format!("-{}{}{}", symbol, space, &amount[1..]),
+                _ => format!("{}{}{}", symbol, space, amount),
+            },
+            Some(symbol) => format!("{}{}{}", amount, space, symbol),
+            None => format!("{} {}", amount, currency),
+        }
+    }
+}
+
+/// Source of formatted values
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DisplayMode {
+    /// All values are formatted using the locale
+    #[default]
+    Local,
+    /// Values are shown as formatted by the api provider (e.g. like on the website of cryptocompare),
+    /// other values are formatted using the locale
+    Provider,
+}
+
+impl FromStr for DisplayMode {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "local" => Ok(DisplayMode::Local),
+            "provider" => Ok(DisplayMode::Provider),
+            _ => Err(AppError::DisplayMode { name: s.into() }),
+        }
+    }
+}
+
+impl DisplayMode {
+    /// Value formatted by the api provider in mode `Provider`, otherwise the locally formatted value
+    pub fn format<F: FnOnce() -> String>(self, provided: Option<&String>, local: F) -> String {
+        match (self, provided) {
+            (DisplayMode::Provider, Some(value)) => value.clone(),
+            _ => local(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::constants::FIAT_LIST;
+
+    fn decimal(s: &str) -> Decimal {
+        s.parse().unwrap()
+    }
+
+    fn locale(name: &str) -> Locale {
+        Locale::by_name(name).unwrap()
+    }
+
+    #[test]
+    fn currencies_of_all_fiats() {
+        for fiat in FIAT_LIST.iter() {
+            assert!(currency_symbol(fiat).is_some(), "no symbol of {}", fiat);
+        }
+        assert_eq!(CURRENCIES.len(), FIAT_LIST.len());
+        assert_eq!(minor_units("EUR"), Some(2));
+        assert_eq!(minor_units("JPY"), Some(0));
+        assert_eq!(minor_units("KWD"), Some(3));
+        assert_eq!(minor_units("BTC"), None);
+        assert_eq!(decimals("BTC"), 8);
+    }
+
+    #[test]
+    fn locale_by_name() {
+        assert_eq!(locale("de_DE.UTF-8"), locale("de"));
+        assert_eq!(locale("de-CH"), locale("de_CH"));
+        assert_eq!(locale("en_US"), Locale::default());
+        for name in LOCALES.iter() {
+            assert!(Locale::by_name(name).is_ok());
+        }
+        assert!(Locale::by_name("xx").is_err());
+        assert!(Locale::by_name("C").is_err());
+    }
+
+    #[test]
+    fn format_numbers() {
+        let value = decimal("-1234567.891");
+        assert_eq!(locale("en").number(&value, 2), "-1,234,567.89");
+        assert_eq!(locale("de").number(&value, 2), "-1.234.567,89");
+        assert_eq!(locale("de_CH").number(&value, 0), "-1’234’568");
+        assert_eq!(locale("fr").number(&value, 1), "-1\u{202f}234\u{202f}567,9");
+        assert_eq!(locale("en").number(&decimal("123"), 2), "123.00");
+        assert_eq!(locale("en").number(&decimal("0.5"), 0), "1");
+    }
+
+    #[test]
+    fn format_prices() {
+        let price = decimal("3200.987");
+        assert_eq!(locale("en").price(&price, "USD"), "$3,200.99");
+        assert_eq!(locale("de").price(&price, "EUR"), "3.200,99 €");
+        assert_eq!(locale("pt").price(&price, "BRL"), "R$ 3.200,99");
+        assert_eq!(locale("de_CH").price(&price, "CHF"), "CHF 3’200.99");
+        assert_eq!(locale("en").price(&decimal("400123.5"), "JPY"), "¥400,124");
+        assert_eq!(locale("en").price(&decimal("1.2345"), "KWD"), "د.ك1.235");
+        assert_eq!(locale("en").price(&decimal("-5"), "USD"), "-$5.00");
+        assert_eq!(locale("en").price(&decimal("0.025"), "BTC"), "0.02500000 BTC");
+    }
+
+    #[test]
+    fn format_tiny_prices() {
+        assert_eq!(locale("en").price(&decimal("0.000012345678"), "USD"), "$0.00001235");
+        assert_eq!(locale("de").price(&decimal("0.00000000012345"), "EUR"), "0,0000000001235 €");
+        assert_eq!(locale("en").price(&decimal("0.5"), "USD"), "$0.5000");
+        assert_eq!(locale("en").price(&decimal("0.000000000126929"), "BTC"), "0.0000000001269 BTC");
+        assert_eq!(locale("en").price(&decimal("0.0000000000000000000001"), "USD"), "<$0.000000000000000001");
+        assert_eq!(locale("de").price(&decimal("0.0000000000000000000001"), "EUR"), "<0,000000000000000001 €");
+        assert_eq!(locale("en").price(&decimal("0.0000000000000000009"), "USD"), "$0.000000000000000001");
+        assert_eq!(locale("en").price(&Decimal::zero(), "USD"), "$0.00");
+    }
+
+    #[test]
+    fn format_changes() {
+        assert_eq!(locale("en").change(&decimal("1.234")), "+1.23%");
+        assert_eq!(locale("de").change(&decimal("-0.797")), "-0,80%");
+        assert_eq!(locale("en").change(&Decimal::zero()), "0.00%");
+    }
+
+    #[test]
+    fn format_compact() {
+        assert_eq!(locale("en").compact(&decimal("56110256905.26"), "USD"), "$56.1B");
+        assert_eq!(locale("de").compact(&decimal("1234567890123"), "EUR"), "1,2T €");
+        assert_eq!(locale("en").compact(&decimal("2500000"), "BTC"), "2.5M BTC");
+        assert_eq!(locale("en").compact(&decimal("1500"), "USD"), "$1.5K");
+        assert_eq!(locale("en").compact(&decimal("999.6"), "USD"), "$1,000");
+    }
+
+    #[test]
+    fn display_modes() {
+        let provided = Some("€ 56.11 B".to_string());
+        let local = || locale("en").compact(&decimal("56110256905.26"), "EUR");
+        assert_eq!(DisplayMode::Provider.format(provided.as_ref(), local), "€ 56.11 B");
+        assert_eq!(DisplayMode::Local.format(provided.as_ref(), local), "€56.1B");
+        assert_eq!(DisplayMode::Provider.format(None, local), "€56.1B");
+        assert_eq!("provider".parse::<DisplayMode>().unwrap(), DisplayMode::Provider);
+        assert!("website".parse::<DisplayMode>().is_err());
+    }
+}
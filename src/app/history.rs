@@ -0,0 +1,543 @@
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Deserializer, Serializer};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{
+    decimal::Decimal,
+    errors::AppError,
+    types::{AppResult, Coins},
+};
+
+// Schema changes, applied in order. The number of applied migrations is stored as `user_version`.
+const MIGRATIONS: [&str; 3] = [
+    "
+    CREATE TABLE quotes (
+        id INTEGER PRIMARY KEY,
+        time INTEGER NOT NULL,
+        provider TEXT NOT NULL,
+        fiat TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        price REAL,
+        percent_change_24h REAL,
+        market_cap REAL
+    );
+    CREATE INDEX quotes_symbol_fiat_time ON quotes (symbol, fiat, time);
+",
+    // one quote per provider, fiat, symbol and time, e.g. to import the same quotes twice
+    "
+    DELETE FROM quotes WHERE id NOT IN (SELECT MAX(id) FROM quotes GROUP BY provider, fiat, symbol, time);
+    CREATE UNIQUE INDEX quotes_provider_fiat_symbol_time ON quotes (provider, fiat, symbol, time);
+",
+    // decimals are stored as text to keep all their digits
+    "
+    CREATE TABLE quotes_text (
+        id INTEGER PRIMARY KEY,
+        time INTEGER NOT NULL,
+        provider TEXT NOT NULL,
+        fiat TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        price TEXT,
+        percent_change_24h TEXT,
+        market_cap TEXT
+    );
+    INSERT INTO quotes_text
+        SELECT id, time, provider, fiat, symbol,
+            CAST(price AS TEXT), CAST(percent_change_24h AS TEXT), CAST(market_cap AS TEXT)
+        FROM quotes;
+    DROP TABLE quotes;
+    ALTER TABLE quotes_text RENAME TO quotes;
+    CREATE INDEX quotes_symbol_fiat_time ON quotes (symbol, fiat, time);
+    CREATE UNIQUE INDEX quotes_provider_fiat_symbol_time ON quotes (provider, fiat, symbol, time);
+",
+];
+
+/// Settings of history stored in config file, e.g.
+///
+/// ```toml
+/// [history]
+/// path = "/home/satoshi/.local/share/wtch-crpts/history.db"
+/// retention_days = 365
+/// downsample_after_days = 7
+/// downsample_minutes = 60
+/// ```
+#[derive(Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct HistorySettings {
+    /// History is stored if it's enabled or a path is set
+    pub enabled: Option<bool>,
+    pub path: Option<String>,
+    /// Quotes older than this are deleted
+    pub retention_days: Option<u64>,
+    /// Quotes older than this are reduced to one quote per `downsample_minutes`
+    pub downsample_after_days: Option<u64>,
+    pub downsample_minutes: Option<u64>,
+}
+
+/// Single stored quote, e.g. to export or import it. Decimals are written as text, since CSV numbers are read as
+/// `f64`, which would drop their digits.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    pub symbol: String,
+    pub fiat: String,
+    pub provider: String,
+    pub timestamp: DateTime<Local>,
+    #[serde(serialize_with = "serialize_text", deserialize_with = "deserialize_text")]
+    pub price: Option<Decimal>,
+    #[serde(serialize_with = "serialize_text", deserialize_with = "deserialize_text")]
+    pub percent_change_24h: Option<Decimal>,
+    #[serde(serialize_with = "serialize_text", deserialize_with = "deserialize_text")]
+    pub market_cap: Option<Decimal>,
+}
+
+fn serialize_text<S: Serializer>(value: &Option<Decimal>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.serialize_some(&value.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn deserialize_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Decimal>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Stored as text with all its digits
+impl ToSql for Decimal {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.to_string()))
+    }
+}
+
+/// Read from text or from numbers, e.g. of quotes stored before decimals were stored as text
+impl FromSql for Decimal {
+    fn column_result(value: ValueRef) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Text(_) => value.as_str()?.parse().map_err(|e| FromSqlError::Other(Box::new(e))),
+            ValueRef::Integer(i) => Ok(Decimal::from(i)),
+            ValueRef::Real(f) => Decimal::from_f64(f).ok_or(FromSqlError::InvalidType),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+impl HistoryRecord {
+    fn from_row(row: &Row) -> rusqlite::Result<HistoryRecord> {
+        Ok(HistoryRecord {
+            timestamp: Local.timestamp_opt(row.get(0)?, 0).unwrap(),
+            provider: row.get(1)?,
+            fiat: row.get(2)?,
+            symbol: row.get(3)?,
+            price: row.get(4)?,
+            percent_change_24h: row.get(5)?,
+            market_cap: row.get(6)?,
+        })
+    }
+}
+
+/// Filter of stored quotes. All quotes are selected by default.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HistoryFilter {
+    pub from: Option<DateTime<Local>>,
+    /// Quotes before this time are selected
+    pub to: Option<DateTime<Local>>,
+    pub symbols: Vec<String>,
+}
+
+/// How long quotes are kept and in which resolution
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Retention {
+    pub max_age: Option<Duration>,
+    pub downsample_after: Option<Duration>,
+    pub downsample_interval: Duration,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            max_age: None,
+            downsample_after: None,
+            downsample_interval: Duration::from_secs(60 * 60),
+        }
+    }
+}
+
+impl From<&HistorySettings> for Retention {
+    fn from(settings: &HistorySettings) -> Self {
+        let days = |d: u64| Duration::from_secs(d * 24 * 60 * 60);
+        Retention {
+            max_age: settings.retention_days.map(days),
+            downsample_after: settings.downsample_after_days.map(days),
+            downsample_interval: settings
+                .downsample_minutes
+                .map(|m| Duration::from_secs(m.max(1) * 60))
+                .unwrap_or_else(|| Retention::default().downsample_interval),
+        }
+    }
+}
+
+/// History of quotes stored in a SQLite database
+pub struct History {
+    conn: Connection,
+    retention: Retention,
+}
+
+impl History {
+    pub fn open<P: AsRef<Path>>(path: P, retention: Retention) -> AppResult<History> {
+        let path = path.as_ref();
+        info!("open history {}", path.display());
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|cause| AppError::HistoryOpen {
+                path: path.display().to_string(),
+                cause,
+            })?;
+        }
+        History::with_connection(Connection::open(path)?, retention)
+    }
+
+    /// Path of history if no other path is given, e.g. `~/.local/share/wtch-crpts/history.db`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("wtch-crpts").join("history.db"))
+    }
+
+    #[cfg(test)]
+    fn open_in_memory(retention: Retention) -> AppResult<History> {
+        History::with_connection(Connection::open_in_memory()?, retention)
+    }
+
+    fn with_connection(conn: Connection, retention: Retention) -> AppResult<History> {
+        let history = History { conn, retention };
+        history.migrate()?;
+        Ok(history)
+    }
+
+    /// Applies all migrations, which are not applied yet
+    fn migrate(&self) -> AppResult<()> {
+        let version: i64 = self.conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            info!("migrate history to version {}", i + 1);
+            self.in_transaction(|conn| {
+                conn.execute_batch(migration)?;
+                conn.pragma_update(None, "user_version", &(i as i64 + 1))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn in_transaction<T, F>(&self, f: F) -> AppResult<T>
+    where
+        F: FnOnce(&Connection) -> rusqlite::Result<T>,
+    {
+        self.conn.execute_batch("BEGIN")?;
+        match f(&self.conn) {
+            Ok(result) => {
+                self.conn.execute_batch("COMMIT")?;
+                Ok(result)
+            }
+            Err(e) => {
+                let _ = self.conn.execute_batch("ROLLBACK");
+                Err(AppError::History(e))
+            }
+        }
+    }
+
+    /// Stores quotes in all fiats of coins fetched at given time and applies retention
+    pub fn record(&self, coins: &Coins, provider: &str, time: &DateTime<Local>) -> AppResult<()> {
+        self.in_transaction(|conn| {
+            let mut insert = conn.prepare(
+                "INSERT OR REPLACE INTO quotes (time, provider, fiat, symbol, price, percent_change_24h, market_cap)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )?;
+            for coin in &coins.list {
+                for (fiat, quote) in &coin.quotes {
+                    insert.execute(params![
+                        time.timestamp(),
+                        provider,
+                        fiat,
+                        coin.symbol,
+                        quote.price,
+                        quote.percent_change_24h,
+                        quote.market_cap,
+                    ])?;
+                }
+            }
+            Ok(())
+        })?;
+        self.prune(time)
+    }
+
+    /// Deletes quotes older than max age and downsamples old quotes
+    fn prune(&self, now: &DateTime<Local>) -> AppResult<()> {
+        let before = |age: Duration| now.timestamp() - age.as_secs() as i64;
+        if let Some(max_age) = self.retention.max_age {
+            self.conn.execute("DELETE FROM quotes WHERE time < ?", params![before(max_age)])?;
+        }
+        if let Some(after) = self.retention.downsample_after {
+            // keeps the latest quote of each interval
+            self.conn.execute(
+                "DELETE FROM quotes WHERE time < ?1 AND id NOT IN (
+                    SELECT MAX(id) FROM quotes WHERE time < ?1
+                    GROUP BY provider, fiat, symbol, time / ?2
+                 )",
+                params![before(after), self.retention.downsample_interval.as_secs() as i64],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Prices of a coin quoted by a provider since given time, e.g. to draw a chart
+    pub fn series(
+        &self,
+        provider: &str,
+        symbol: &str,
+        fiat: &str,
+        since: &DateTime<Local>,
+    ) -> AppResult<Vec<(DateTime<Local>, f64)>> {
+        let mut statement = self.conn.prepare(
+            "SELECT time, price FROM quotes
+             WHERE provider = ? AND symbol = ? AND fiat = ? AND time >= ? AND price IS NOT NULL
+             ORDER BY time",
+        )?;
+        let rows = statement.query_map(params![provider, symbol, fiat, since.timestamp()], |row| {
+            let price: Decimal = row.get(1)?;
+            Ok((Local.timestamp_opt(row.get(0)?, 0).unwrap(), price.to_f64()))
+        })?;
+        let series = rows.collect::<rusqlite::Result<_>>()?;
+        Ok(series)
+    }
+
+    /// Stored quotes selected by filter, ordered by time
+    pub fn records(&self, filter: &HistoryFilter) -> AppResult<Vec<HistoryRecord>> {
+        let mut sql = String::from(
+            "SELECT time, provider, fiat, symbol, price, percent_change_24h, market_cap FROM quotes WHERE 1 = 1",
+        );
+        let mut values: Vec<Box<dyn ToSql>> = vec![];
+        if let Some(from) = filter.from {
+            sql.push_str(" AND time >= ?");
+            values.push(Box::new(from.timestamp()));
+        }
+        if let Some(to) = filter.to {
+            sql.push_str(" AND time < ?");
+            values.push(Box::new(to.timestamp()));
+        }
+        if !filter.symbols.is_empty() {
+            let placeholders = vec!["?"; filter.symbols.len()].join(", ");
+            sql.push_str(&format!(" AND symbol IN ({})", placeholders));
+            values.extend(filter.symbols.iter().map(|s| Box::new(s.clone()) as Box<dyn ToSql>));
+        }
+        sql.push_str(" ORDER BY time, symbol");
+        let mut statement = self.conn.prepare(&sql)?;
+        let rows = statement.query_map(values.iter().map(|v| v.as_ref()), HistoryRecord::from_row)?;
+        let records = rows.collect::<rusqlite::Result<_>>()?;
+        Ok(records)
+    }
+
+    /// Stores imported quotes. Quotes, which are already stored, are skipped. Returns number of stored quotes.
+    pub fn import<I>(&self, records: I) -> AppResult<usize>
+    where
+        I: IntoIterator<Item = HistoryRecord>,
+    {
+        self.in_transaction(|conn| {
+            let mut insert = conn.prepare(
+                "INSERT OR IGNORE INTO quotes (time, provider, fiat, symbol, price, percent_change_24h, market_cap)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )?;
+            let mut count = 0;
+            for r in records {
+                count += insert.execute(params![
+                    r.timestamp.timestamp(),
+                    r.provider,
+                    r.fiat,
+                    r.symbol,
+                    r.price,
+                    r.percent_change_24h,
+                    r.market_cap,
+                ])?;
+            }
+            Ok(count)
+        })
+    }
+
+    /// Change of price in percent between the latest quote of a provider and its latest quote at least `period`
+    /// before it. It's `None` if there are not enough quotes yet.
+    pub fn change(&self, provider: &str, symbol: &str, fiat: &str, period: Duration) -> AppResult<Option<Decimal>> {
+        let latest: Option<(i64, Decimal)> = self
+            .conn
+            .query_row(
+                "SELECT time, price FROM quotes
+                 WHERE provider = ? AND symbol = ? AND fiat = ? AND price IS NOT NULL
+                 ORDER BY time DESC LIMIT 1",
+                params![provider, symbol, fiat],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let (time, price) = match latest {
+            Some(latest) => latest,
+            None => return Ok(None),
+        };
+        let past: Option<Decimal> = self
+            .conn
+            .query_row(
+                "SELECT price FROM quotes
+                 WHERE provider = ? AND symbol = ? AND fiat = ? AND price IS NOT NULL AND time <= ?
+                 ORDER BY time DESC LIMIT 1",
+                params![provider, symbol, fiat, time - period.as_secs() as i64],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let hundred = Decimal::from(100);
+        Ok(past.and_then(|p| (&price - &p).checked_div(&p)).map(|c| &c * &hundred))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::types::CoinBuilder;
+
+    fn coins(price: f64) -> Coins {
+        Coins::new(vec![CoinBuilder::default().symbol("BTC").price("EUR", price).build().unwrap()])
+    }
+
+    fn time(seconds: i64) -> DateTime<Local> {
+        Local.timestamp_opt(1_549_733_663 + seconds, 0).unwrap()
+    }
+
+    fn count(history: &History) -> i64 {
+        history.conn.query_row("SELECT COUNT(*) FROM quotes", params![], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn migrate_once() {
+        let history = History::open_in_memory(Retention::default()).unwrap();
+        history.migrate().unwrap();
+        let version: i64 = history.conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn migrate_real_prices() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.execute_batch(MIGRATIONS[1]).unwrap();
+        conn.pragma_update(None, "user_version", &2).unwrap();
+        conn.execute(
+            "INSERT INTO quotes (time, provider, fiat, symbol, price) VALUES (?, ?, ?, ?, ?)",
+            params![time(0).timestamp(), "coinmarketcap", "EUR", "BTC", 3200.5],
+        )
+        .unwrap();
+        let history = History::with_connection(conn, Retention::default()).unwrap();
+        let records = history.records(&HistoryFilter::default()).unwrap();
+        assert_eq!(records[0].price, Some("3200.5".parse().unwrap()));
+        assert_eq!(records[0].market_cap, None);
+    }
+
+    #[test]
+    fn record_all_digits() {
+        let history = History::open_in_memory(Retention::default()).unwrap();
+        let price: Decimal = "0.000012345678901234567891".parse().unwrap();
+        let coin = CoinBuilder::default().symbol("SHIB").price("EUR", price.clone()).build().unwrap();
+        let coins = Coins::new(vec![coin]);
+        history.record(&coins, "coinmarketcap", &time(0)).unwrap();
+        let records = history.records(&HistoryFilter::default()).unwrap();
+        assert_eq!(records[0].price, Some(price.clone()));
+        history.record(&coins, "coinmarketcap", &time(60)).unwrap();
+        let change = history.change("coinmarketcap", "SHIB", "EUR", Duration::from_secs(60)).unwrap();
+        assert_eq!(change, Some(Decimal::zero()));
+    }
+
+    #[test]
+    fn change_over_period() {
+        let history = History::open_in_memory(Retention::default()).unwrap();
+        let change = |provider: &str, fiat: &str, seconds: u64| {
+            history.change(provider, "BTC", fiat, Duration::from_secs(seconds)).unwrap()
+        };
+        assert_eq!(change("coinmarketcap", "EUR", 60), None);
+        history.record(&coins(100.0), "coinmarketcap", &time(0)).unwrap();
+        history.record(&coins(150.0), "coinmarketcap", &time(60)).unwrap();
+        history.record(&coins(110.0), "coinmarketcap", &time(120)).unwrap();
+        // quotes of other providers (e.g. replayed ones) aren't mixed in
+        history.record(&coins(1.0), "coinmarketcap-replay", &time(90)).unwrap();
+
+        assert_eq!(change("coinmarketcap", "EUR", 120), Some(Decimal::from(10)));
+        assert_eq!(change("coinmarketcap", "EUR", 90), Some(Decimal::from(10)));
+        assert_eq!(change("coinmarketcap", "EUR", 300), None);
+        assert_eq!(change("coinmarketcap", "USD", 60), None);
+        assert_eq!(change("coinmarketcap-replay", "EUR", 60), None);
+
+        let series = history.series("coinmarketcap", "BTC", "EUR", &time(60)).unwrap();
+        assert_eq!(series, vec![(time(60), 150.0), (time(120), 110.0)]);
+        let series = history.series("coinmarketcap-replay", "BTC", "EUR", &time(0)).unwrap();
+        assert_eq!(series, vec![(time(90), 1.0)]);
+    }
+
+    #[test]
+    fn record_all_fiats() {
+        let history = History::open_in_memory(Retention::default()).unwrap();
+        let coins = Coins::new(vec![CoinBuilder::default()
+            .symbol("BTC")
+            .price("EUR", 100.0)
+            .price("USD", 120.0)
+            .build()
+            .unwrap()]);
+        history.record(&coins, "coinmarketcap", &time(0)).unwrap();
+        assert_eq!(history.series("coinmarketcap", "BTC", "EUR", &time(0)).unwrap(), vec![(time(0), 100.0)]);
+        assert_eq!(history.series("coinmarketcap", "BTC", "USD", &time(0)).unwrap(), vec![(time(0), 120.0)]);
+    }
+
+    #[test]
+    fn filter_and_import_records() {
+        let history = History::open_in_memory(Retention::default()).unwrap();
+        history.record(&coins(100.0), "coinmarketcap", &time(0)).unwrap();
+        history.record(&coins(110.0), "coinmarketcap", &time(60)).unwrap();
+        history.record(&coins(120.0), "coinmarketcap", &time(120)).unwrap();
+
+        let filter = HistoryFilter {
+            from: Some(time(60)),
+            to: Some(time(120)),
+            symbols: vec!["BTC".into()],
+        };
+        let records = history.records(&filter).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].timestamp, time(60));
+        assert_eq!(records[0].price, Some(Decimal::from(110)));
+        assert_eq!(records[0].provider, "coinmarketcap");
+        let filter = HistoryFilter {
+            symbols: vec!["ETH".into()],
+            ..HistoryFilter::default()
+        };
+        assert!(history.records(&filter).unwrap().is_empty());
+
+        let all = history.records(&HistoryFilter::default()).unwrap();
+        let imported = History::open_in_memory(Retention::default()).unwrap();
+        assert_eq!(imported.import(all.clone()).unwrap(), 3);
+        // already imported quotes are skipped
+        assert_eq!(imported.import(all.clone()).unwrap(), 0);
+        assert_eq!(imported.records(&HistoryFilter::default()).unwrap(), all);
+    }
+
+    #[test]
+    fn apply_retention() {
+        let retention = Retention {
+            max_age: Some(Duration::from_secs(3600)),
+            downsample_after: Some(Duration::from_secs(600)),
+            downsample_interval: Duration::from_secs(300),
+        };
+        let history = History::open_in_memory(retention).unwrap();
+        // one quote per minute for 90 minutes
+        for minute in 0..90 {
+            history.record(&coins(minute as f64), "coinmarketcap", &time(minute * 60)).unwrap();
+        }
+        // last 11 minutes in full resolution, 50 minutes before in 11 buckets of 5 minutes
+        assert_eq!(count(&history), 22);
+        let series = history.series("coinmarketcap", "BTC", "EUR", &time(0)).unwrap();
+        assert!(series[0].0 >= time(29 * 60));
+        assert_eq!(series[series.len() - 11].0, time(79 * 60));
+    }
+}
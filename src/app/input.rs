@@ -4,42 +4,52 @@ extern crate tui;
 use std::io;
 use std::sync::mpsc;
 use std::thread;
-use termion::event::Key;
+use std::time::Duration;
+use termion::event::{Event, Key, MouseEvent};
 use termion::input::TermRead;
 
 pub enum InputEvent {
-    Exit,
     InputKey(Key),
+    InputMouse(MouseEvent),
+    Tick,
 }
 
 pub struct InputChannel {
     pub rx: mpsc::Receiver<InputEvent>,
     #[allow(dead_code)]
     handle: thread::JoinHandle<()>,
+    #[allow(dead_code)]
+    tick_handle: thread::JoinHandle<()>,
 }
 
 impl InputChannel {
-    pub fn new() -> InputChannel {
+    pub fn new(tick_rate: Duration) -> InputChannel {
         let (tx, rx) = mpsc::channel();
         let handle = {
             let tx = tx.clone();
             thread::spawn(move || {
                 let stdin = io::stdin();
-                for evt in stdin.keys() {
-                    if let Ok(key) = evt {
-                            let inp_event = if key == Key::Char('q') {
-                                InputEvent::Exit
-                            } else {
-                                InputEvent::InputKey(key)
-                            };
-                            if tx.send(inp_event).is_err() {
-                                return;
-                            }
-                        }
+                for evt in stdin.events() {
+                    let inp_event = match evt {
+                        Ok(Event::Key(key)) => InputEvent::InputKey(key),
+                        Ok(Event::Mouse(mouse)) => InputEvent::InputMouse(mouse),
+                        _ => continue,
+                    };
+                    if tx.send(inp_event).is_err() {
+                        return;
                     }
                 }
-            )
+            })
+        };
+        let tick_handle = {
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                thread::sleep(tick_rate);
+                if tx.send(InputEvent::Tick).is_err() {
+                    return;
+                }
+            })
         };
-        InputChannel { rx, handle }
+        InputChannel { rx, handle, tick_handle }
     }
 }
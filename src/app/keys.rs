@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use termion::event::Key;
+
+use super::{errors::AppError, types::AppResult};
+
+// Names of built-in key maps
+pub const KEYMAPS: [&str; 3] = ["default", "vim", "emacs"];
+
+/// Actions which can be triggered by keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Refresh,
+    Sort,
+    Search,
+    Detail,
+    Convert,
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [
+        Action::Quit,
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::Refresh,
+        Action::Sort,
+        Action::Search,
+        Action::Detail,
+        Action::Convert,
+        Action::Help,
+    ];
+
+    /// Name of an action as used in config file
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::Refresh => "refresh",
+            Action::Sort => "sort",
+            Action::Search => "search",
+            Action::Detail => "detail",
+            Action::Convert => "convert",
+            Action::Help => "help",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().cloned().find(|a| a.name() == name)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Up => "Select previous coin",
+            Action::Down => "Select next coin",
+            Action::PageUp => "Move one page up",
+            Action::PageDown => "Move one page down",
+            Action::Top => "Select first coin",
+            Action::Bottom => "Select last coin",
+            Action::Refresh => "Refresh data",
+            Action::Sort => "Change sort order",
+            Action::Search => "Search for a symbol",
+            Action::Detail => "Show or hide details of selected coin",
+            Action::Convert => "Convert an amount between currencies",
+            Action::Help => "Show or hide this help",
+        }
+    }
+}
+
+/// Bindings of keys to actions
+///
+/// Key bindings of a built-in key map can be changed in config file, e.g.
+///
+/// ```toml
+/// keymap = "vim"
+///
+/// [keys]
+/// refresh = ["r", "f5"]
+/// quit = ["q", "ctrl-c"]
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMap {
+    bindings: Vec<(Action, Vec<Key>)>,
+}
+
+impl KeyMap {
+    fn from_bindings(bindings: &[(Action, &[Key])]) -> Self {
+        KeyMap {
+            bindings: bindings.iter().map(|(a, keys)| (*a, keys.to_vec())).collect(),
+        }
+    }
+
+    pub fn standard() -> Self {
+        KeyMap::from_bindings(&[
+            (Action::Quit, &[Key::Char('q'), Key::Ctrl('c')]),
+            (Action::Up, &[Key::Up]),
+            (Action::Down, &[Key::Down]),
+            (Action::PageUp, &[Key::PageUp]),
+            (Action::PageDown, &[Key::PageDown]),
+            (Action::Top, &[Key::Home]),
+            (Action::Bottom, &[Key::End]),
+            (Action::Refresh, &[Key::Char('r')]),
+            (Action::Sort, &[Key::Char('s')]),
+            (Action::Search, &[Key::Char('/')]),
+            (Action::Detail, &[Key::Char('\n')]),
+            (Action::Convert, &[Key::Char('c')]),
+            (Action::Help, &[Key::Char('?')]),
+        ])
+    }
+
+    pub fn vim() -> Self {
+        KeyMap::from_bindings(&[
+            (Action::Quit, &[Key::Char('q'), Key::Ctrl('c')]),
+            (Action::Up, &[Key::Char('k'), Key::Up]),
+            (Action::Down, &[Key::Char('j'), Key::Down]),
+            (Action::PageUp, &[Key::Ctrl('b'), Key::PageUp]),
+            (Action::PageDown, &[Key::Ctrl('f'), Key::PageDown]),
+            (Action::Top, &[Key::Char('g'), Key::Home]),
+            (Action::Bottom, &[Key::Char('G'), Key::End]),
+            (Action::Refresh, &[Key::Char('r')]),
+            (Action::Sort, &[Key::Char('s')]),
+            (Action::Search, &[Key::Char('/')]),
+            (Action::Detail, &[Key::Char('l'), Key::Char('\n')]),
+            (Action::Convert, &[Key::Char('c')]),
+            (Action::Help, &[Key::Char('?')]),
+        ])
+    }
+
+    pub fn emacs() -> Self {
+        KeyMap::from_bindings(&[
+            (Action::Quit, &[Key::Char('q'), Key::Ctrl('c')]),
+            (Action::Up, &[Key::Ctrl('p'), Key::Up]),
+            (Action::Down, &[Key::Ctrl('n'), Key::Down]),
+            (Action::PageUp, &[Key::Alt('v'), Key::PageUp]),
+            (Action::PageDown, &[Key::Ctrl('v'), Key::PageDown]),
+            (Action::Top, &[Key::Alt('<'), Key::Home]),
+            (Action::Bottom, &[Key::Alt('>'), Key::End]),
+            (Action::Refresh, &[Key::Char('g')]),
+            (Action::Sort, &[Key::Char('s')]),
+            (Action::Search, &[Key::Ctrl('s')]),
+            (Action::Detail, &[Key::Char('\n')]),
+            (Action::Convert, &[Key::Alt('c')]),
+            (Action::Help, &[Key::Char('?'), Key::Ctrl('h')]),
+        ])
+    }
+
+    /// Key map by name of a built-in key map, with custom bindings (action name -> key names) applied on top
+    pub fn by_name(name: &str, custom: &HashMap<String, Vec<String>>) -> AppResult<Self> {
+        let mut keymap = match name {
+            "default" => KeyMap::standard(),
+            "vim" => KeyMap::vim(),
+            "emacs" => KeyMap::emacs(),
+            _ => return Err(AppError::KeyMap { name: name.into() }),
+        };
+        for (action_name, key_names) in custom {
+            let action = Action::from_name(action_name).ok_or_else(|| AppError::KeyAction {
+                name: action_name.clone(),
+            })?;
+            let keys = key_names
+                .iter()
+                .map(|k| parse_key(k).ok_or_else(|| AppError::KeyBinding { key: k.clone() }))
+                .collect::<AppResult<Vec<Key>>>()?;
+            keymap.bind(action, keys);
+        }
+        Ok(keymap)
+    }
+
+    /// Replaces all keys of an action. Keys bound to other actions before are removed from those.
+    fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        for (a, ks) in self.bindings.iter_mut() {
+            if *a != action {
+                ks.retain(|k| !keys.contains(k));
+            }
+        }
+        match self.bindings.iter_mut().find(|(a, _)| *a == action) {
+            Some((_, ks)) => *ks = keys,
+            None => self.bindings.push((action, keys)),
+        }
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> Vec<Key> {
+        self.bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map(|(_, keys)| keys.clone())
+            .unwrap_or_default()
+    }
+
+    /// Lines of key names and descriptions of all bound actions
+    pub fn help(&self) -> Vec<(String, &'static str)> {
+        self.bindings
+            .iter()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let names: Vec<String> = keys.iter().map(|k| key_name(*k)).collect();
+                (names.join(", "), action.description())
+            })
+            .collect()
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap::standard()
+    }
+}
+
+/// Parses a key by its name, e.g. `j`, `enter`, `pagedown`, `f5`, `ctrl-n` or `alt-v`
+pub fn parse_key(name: &str) -> Option<Key> {
+    let single_char = |s: &str| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+    if let Some(c) = single_char(name) {
+        return Some(Key::Char(c));
+    }
+    let lower = name.to_lowercase();
+    if lower.starts_with("ctrl-") {
+        return name.get(5..).and_then(single_char).map(|c| Key::Ctrl(c.to_ascii_lowercase()));
+    }
+    if lower.starts_with("alt-") {
+        return name.get(4..).and_then(single_char).map(Key::Alt);
+    }
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return if (1..=12).contains(&n) { Some(Key::F(n)) } else { None };
+    }
+    let key = match lower.as_str() {
+        "enter" => Key::Char('\n'),
+        "tab" => Key::Char('\t'),
+        "space" => Key::Char(' '),
+        "esc" => Key::Esc,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "insert" => Key::Insert,
+        "up" => Key::Up,
+        "down" => Key::Down,
+        "left" => Key::Left,
+        "right" => Key::Right,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
+
+/// Name of a key as it can be parsed by `parse_key`
+pub fn key_name(key: Key) -> String {
+    match key {
+        Key::Char('\n') => "enter".into(),
+        Key::Char('\t') => "tab".into(),
+        Key::Char(' ') => "space".into(),
+        Key::Char(c) => c.to_string(),
+        Key::Ctrl(c) => format!("ctrl-{}", c),
+        Key::Alt(c) => format!("alt-{}", c),
+        Key::F(n) => format!("f{}", n),
+        Key::Esc => "esc".into(),
+        Key::Backspace => "backspace".into(),
+        Key::Delete => "delete".into(),
+        Key::Insert => "insert".into(),
+        Key::Up => "up".into(),
+        Key::Down => "down".into(),
+        Key::Left => "left".into(),
+        Key::Right => "right".into(),
+        Key::Home => "home".into(),
+        Key::End => "end".into(),
+        Key::PageUp => "pageup".into(),
+        Key::PageDown => "pagedown".into(),
+        _ => "?".into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!(parse_key("j"), Some(Key::Char('j')));
+        assert_eq!(parse_key("G"), Some(Key::Char('G')));
+        assert_eq!(parse_key("enter"), Some(Key::Char('\n')));
+        assert_eq!(parse_key("PageDown"), Some(Key::PageDown));
+        assert_eq!(parse_key("ctrl-N"), Some(Key::Ctrl('n')));
+        assert_eq!(parse_key("alt-<"), Some(Key::Alt('<')));
+        assert_eq!(parse_key("f5"), Some(Key::F(5)));
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key("foo"), None);
+    }
+
+    #[test]
+    fn key_names_can_be_parsed() {
+        for keymap in &[KeyMap::standard(), KeyMap::vim(), KeyMap::emacs()] {
+            for action in Action::ALL.iter() {
+                for key in keymap.keys(*action) {
+                    assert_eq!(parse_key(&key_name(key)), Some(key));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn keymap_vim() {
+        let keymap = KeyMap::by_name("vim", &HashMap::new()).unwrap();
+        assert_eq!(keymap.action(Key::Char('j')), Some(Action::Down));
+        assert_eq!(keymap.action(Key::Char('k')), Some(Action::Up));
+        assert_eq!(keymap.action(Key::Char('G')), Some(Action::Bottom));
+        assert_eq!(keymap.action(Key::Char('x')), None);
+    }
+
+    #[test]
+    fn keymap_custom_bindings() {
+        let mut custom = HashMap::new();
+        custom.insert("refresh".to_string(), vec!["f5".to_string(), "q".to_string()]);
+        let keymap = KeyMap::by_name("default", &custom).unwrap();
+        assert_eq!(keymap.action(Key::F(5)), Some(Action::Refresh));
+        assert_eq!(keymap.action(Key::Char('q')), Some(Action::Refresh));
+        assert_eq!(keymap.action(Key::Char('r')), None);
+        assert_eq!(keymap.keys(Action::Quit), vec![Key::Ctrl('c')]);
+
+        let mut unknown_action = HashMap::new();
+        unknown_action.insert("jump".to_string(), vec!["x".to_string()]);
+        assert!(KeyMap::by_name("default", &unknown_action).is_err());
+
+        let mut unknown_key = HashMap::new();
+        unknown_key.insert("quit".to_string(), vec!["hyper-x".to_string()]);
+        assert!(KeyMap::by_name("default", &unknown_key).is_err());
+
+        assert!(KeyMap::by_name("nano", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn keymap_help() {
+        let keymap = KeyMap::standard();
+        let help = keymap.help();
+        assert_eq!(help.len(), Action::ALL.len());
+        assert_eq!(help[0], ("q, ctrl-c".to_string(), "Quit"));
+    }
+}
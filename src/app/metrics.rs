@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{
+    config::Config,
+    decimal::Decimal,
+    errors::AppError,
+    server::{self, HttpResponse},
+    signal::sleep_while_running,
+    types::{AppResult, CoinList, Quote},
+};
+
+// Address to serve metrics if no other address is given
+pub const DEFAULT_METRICS_ADDR: &str = "127.0.0.1:9184";
+
+const PREFIX: &str = "wtch_crpts";
+
+// Name, help and value of a gauge per coin and fiat
+type CoinGauge = (&'static str, &'static str, fn(&Quote) -> Option<&Decimal>);
+
+/// Latest data and statistics of fetching coins
+#[derive(Debug, Default)]
+pub struct Metrics {
+    provider: String,
+    coins: CoinList,
+    fetch_duration: Option<Duration>,
+    fetches: u64,
+    errors: BTreeMap<&'static str, u64>,
+}
+
+impl Metrics {
+    pub fn new(provider: &str) -> Self {
+        Metrics {
+            provider: provider.into(),
+            ..Metrics::default()
+        }
+    }
+
+    pub fn record_success(&mut self, coins: CoinList, duration: Duration) {
+        self.fetches += 1;
+        self.fetch_duration = Some(duration);
+        self.coins = coins;
+    }
+
+    pub fn record_error(&mut self, error: &AppError, duration: Duration) {
+        self.fetches += 1;
+        self.fetch_duration = Some(duration);
+        *self.errors.entry(error.kind()).or_insert(0) += 1;
+    }
+
+    /// Response to a request of given url, which serves metrics at `/metrics`
+    pub fn route(&self, url: &str) -> HttpResponse {
+        // query strings are ignored
+        match url.split('?').next().unwrap_or("") {
+            "/metrics" => HttpResponse::new(200, "text/plain; version=0.0.4", self.render()),
+            _ => HttpResponse::not_found(),
+        }
+    }
+
+    /// Metrics in Prometheus text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let provider = escape(&self.provider);
+
+        let coin_gauges: [CoinGauge; 3] = [
+            ("price", "Price of a cryptocurrency", |q| q.price.as_ref()),
+            (
+                "change_24h_percent",
+                "Change of price in last 24 hours in percent",
+                |q| q.percent_change_24h.as_ref(),
+            ),
+            ("market_cap", "Market capitalization of a cryptocurrency", |q| q.market_cap.as_ref()),
+        ];
+        for (name, help, value) in coin_gauges.iter() {
+            header(&mut out, name, help, "gauge");
+            for coin in &self.coins {
+                for (fiat, quote) in &coin.quotes {
+                    if let Some(v) = value(quote) {
+                        let _ = writeln!(
+                            out,
+                            "{}_{}{{symbol=\"{}\",fiat=\"{}\",provider=\"{}\"}} {}",
+                            PREFIX,
+                            name,
+                            escape(&coin.symbol),
+                            escape(fiat),
+                            provider,
+                            v
+                        );
+                    }
+                }
+            }
+        }
+
+        header(&mut out, "fetch_duration_seconds", "Duration of latest request to api provider", "gauge");
+        if let Some(duration) = self.fetch_duration {
+            let seconds = duration.as_secs() as f64 + f64::from(duration.subsec_micros()) / 1_000_000.0;
+            let _ = writeln!(out, "{}_fetch_duration_seconds{{provider=\"{}\"}} {}", PREFIX, provider, seconds);
+        }
+
+        header(&mut out, "fetches_total", "Number of requests to api provider", "counter");
+        let _ = writeln!(out, "{}_fetches_total{{provider=\"{}\"}} {}", PREFIX, provider, self.fetches);
+
+        header(&mut out, "fetch_errors_total", "Number of failed requests to api provider by error", "counter");
+        for (kind, count) in &self.errors {
+            let _ = writeln!(
+                out,
+                "{}_fetch_errors_total{{provider=\"{}\",error=\"{}\"}} {}",
+                PREFIX, provider, kind, count
+            );
+        }
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, help: &str, metric_type: &str) {
+    let _ = writeln!(out, "# HELP {}_{} {}", PREFIX, name, help);
+    let _ = writeln!(out, "# TYPE {}_{} {}", PREFIX, name, metric_type);
+}
+
+/// Escapes a label value
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Serves metrics at `/metrics` and refreshes them in an interval until `running` is set to `false`
+pub fn serve_metrics(config: &Config, addr: &str, running: Arc<AtomicBool>) -> AppResult<()> {
+    let metrics = Arc::new(Mutex::new(Metrics::new(config.api.get_name())));
+    {
+        let metrics = metrics.clone();
+        server::spawn(addr, move |url| match metrics.lock() {
+            Ok(metrics) => metrics.route(url),
+            Err(_) => HttpResponse::new(500, "text/plain", "Metrics are not available"),
+        })?;
+    }
+
+    while running.load(Ordering::SeqCst) {
+        let started = Instant::now();
+        let result = config.fetch_coins();
+        let duration = started.elapsed();
+        if let Ok(mut m) = metrics.lock() {
+            match result {
+                Ok(coins) => m.record_success(coins.list, duration),
+                Err(e) => {
+                    warn!("failed to get data {}", e);
+                    m.record_error(&e, duration);
+                }
+            }
+        }
+        sleep_while_running(&running, started, config.refresh_interval);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::types::{CoinBuilder, QuoteBuilder};
+
+    #[test]
+    fn render_metrics() {
+        let mut metrics = Metrics::new("coinmarketcap");
+        let quote = QuoteBuilder::default()
+            .price(Decimal::from(3200.5))
+            .percent_change_24h(Decimal::from(-0.5))
+            .market_cap(Decimal::from(56_110_256_905.0))
+            .build()
+            .unwrap();
+        let coins = vec![
+            CoinBuilder::default().symbol("BTC").quote("EUR", quote).price("USD", 3650.25).build().unwrap(),
+            CoinBuilder::default().symbol("LTC").price("EUR", 37.25).build().unwrap(),
+        ];
+        metrics.record_success(coins, Duration::from_millis(250));
+        metrics.record_error(&AppError::ApiParseMap { key: "x".into() }, Duration::from_millis(500));
+        let result = metrics.render();
+
+        assert!(result.contains("# TYPE wtch_crpts_price gauge\n"));
+        assert!(result.contains("wtch_crpts_price{symbol=\"BTC\",fiat=\"EUR\",provider=\"coinmarketcap\"} 3200.5\n"));
+        assert!(result.contains("wtch_crpts_price{symbol=\"BTC\",fiat=\"USD\",provider=\"coinmarketcap\"} 3650.25\n"));
+        assert!(result.contains("wtch_crpts_price{symbol=\"LTC\",fiat=\"EUR\",provider=\"coinmarketcap\"} 37.25\n"));
+        assert!(result.contains(
+            "wtch_crpts_change_24h_percent{symbol=\"BTC\",fiat=\"EUR\",provider=\"coinmarketcap\"} -0.5\n"
+        ));
+        assert!(!result.contains("wtch_crpts_change_24h_percent{symbol=\"LTC\""));
+        assert!(result.contains(
+            "wtch_crpts_market_cap{symbol=\"BTC\",fiat=\"EUR\",provider=\"coinmarketcap\"} 56110256905\n"
+        ));
+        assert!(result.contains("wtch_crpts_fetch_duration_seconds{provider=\"coinmarketcap\"} 0.5\n"));
+        assert!(result.contains("wtch_crpts_fetches_total{provider=\"coinmarketcap\"} 2\n"));
+        assert!(result.contains(
+            "wtch_crpts_fetch_errors_total{provider=\"coinmarketcap\",error=\"api_parse_map\"} 1\n"
+        ));
+    }
+
+    #[test]
+    fn route_metrics() {
+        let metrics = Metrics::new("coinmarketcap");
+        assert_eq!(metrics.route("/metrics").status, 200);
+        assert_eq!(metrics.route("/metrics?x=1").body, metrics.render());
+        assert_eq!(metrics.route("/coins").status, 404);
+    }
+
+    #[test]
+    fn escape_labels() {
+        assert_eq!(escape("a\"b\\c\nd"), "a\\\"b\\\\c\\nd");
+    }
+}
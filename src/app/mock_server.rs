@@ -0,0 +1,227 @@
+use rand::Rng;
+use reqwest::Url;
+use serde_json::json;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use super::{
+    api::fixture::read_fixture,
+    errors::AppError,
+    server::{self, HttpResponse},
+    types::AppResult,
+};
+
+// Address to serve mock data if no other address is given
+pub const DEFAULT_MOCK_ADDR: &str = "127.0.0.1:3000";
+
+// Failures, which can be injected to responses
+pub const FAILURES: [&str; 4] = ["unauthorized", "rate-limit", "server-error", "malformed"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Failure {
+    Unauthorized,
+    RateLimit,
+    ServerError,
+    /// Successful response with invalid JSON
+    Malformed,
+}
+
+impl FromStr for Failure {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unauthorized" => Ok(Failure::Unauthorized),
+            "rate-limit" => Ok(Failure::RateLimit),
+            "server-error" => Ok(Failure::ServerError),
+            "malformed" => Ok(Failure::Malformed),
+            _ => Err(AppError::Failure { name: s.into() }),
+        }
+    }
+}
+
+impl Failure {
+    fn response(self) -> HttpResponse {
+        let error = |status: u16, message: &str| {
+            let body = json!({ "status": { "error_code": status, "error_message": message } });
+            HttpResponse::json(status, body.to_string())
+        };
+        match self {
+            Failure::Unauthorized => error(401, "This API Key is invalid."),
+            Failure::RateLimit => error(429, "You've exceeded your API Key's HTTP request rate limit."),
+            Failure::ServerError => error(500, "An internal server error occurred."),
+            Failure::Malformed => HttpResponse::json(200, r#"{"data": {"BTC": "#),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockOptions {
+    /// Delay of every response
+    pub latency: Duration,
+    /// Maximum change of prices in percent
+    pub jitter: f64,
+    pub failure: Option<Failure>,
+    /// Probability of a failure in percent
+    pub failure_rate: f64,
+}
+
+impl Default for MockOptions {
+    fn default() -> Self {
+        MockOptions {
+            latency: Duration::from_millis(0),
+            jitter: 0.0,
+            failure: None,
+            failure_rate: 100.0,
+        }
+    }
+}
+
+/// Http server, which serves fixtures on the paths of the real api providers
+pub struct MockServer {
+    fixtures: PathBuf,
+    options: MockOptions,
+}
+
+impl MockServer {
+    pub fn new(fixtures: PathBuf, options: MockOptions) -> Self {
+        MockServer { fixtures, options }
+    }
+
+    /// Response to a request of given url
+    pub fn route(&self, url: &str) -> HttpResponse {
+        let url = match Url::parse("http://localhost").and_then(|base| base.join(url)) {
+            Ok(url) => url,
+            Err(_) => return HttpResponse::not_found(),
+        };
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.split(',').map(String::from).collect::<Vec<_>>())
+        };
+        let (provider, fiats) = match url.path().trim_end_matches('/') {
+            "/quotes" | "/quotes/latest" => ("coinmarketcap", param("convert")),
+            "/pricemultifull" => ("cryptocompare", param("tsyms")),
+            _ => return HttpResponse::not_found(),
+        };
+        if let Some(failure) = self.options.failure {
+            if rand::thread_rng().gen_range(0.0, 100.0) < self.options.failure_rate {
+                return failure.response();
+            }
+        }
+        // fixtures of several fiats are recorded by requesting all of them at once
+        let fiats = fiats.unwrap_or_else(|| vec!["USD".into()]);
+        let fiats: Vec<&str> = fiats.iter().map(String::as_str).collect();
+        match read_fixture(&self.fixtures, provider, &fiats) {
+            Ok(mut response) => {
+                if self.options.jitter > 0.0 {
+                    jitter_prices(&mut response, self.options.jitter);
+                }
+                HttpResponse::json(200, response.to_string())
+            }
+            Err(e) => {
+                warn!("failed to read fixture {}", e);
+                HttpResponse::json(404, json!({ "error": e.to_string() }).to_string())
+            }
+        }
+    }
+}
+
+/// Changes all prices (`price` or `PRICE`) randomly by up to `jitter` percent
+fn jitter_prices(value: &mut serde_json::Value, jitter: f64) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, v) in map.iter_mut() {
+                match v.as_f64() {
+                    Some(price) if key == "price" || key == "PRICE" => {
+                        let factor = 1.0 + rand::thread_rng().gen_range(-jitter, jitter) / 100.0;
+                        *v = json!(price * factor);
+                    }
+                    _ => jitter_prices(v, jitter),
+                }
+            }
+        }
+        serde_json::Value::Array(list) => list.iter_mut().for_each(|v| jitter_prices(v, jitter)),
+        _ => {}
+    }
+}
+
+/// Serves fixtures until `running` is set to `false`
+pub fn serve_mock(mock: MockServer, addr: &str, running: Arc<AtomicBool>) -> AppResult<()> {
+    server::spawn(addr, move |url| {
+        thread::sleep(mock.options.latency);
+        mock.route(url)
+    })?;
+    while running.load(Ordering::SeqCst) {
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::api::fixture::DEFAULT_FIXTURES_DIR;
+    use std::path::Path;
+
+    fn mock(options: MockOptions) -> MockServer {
+        MockServer::new(Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_FIXTURES_DIR), options)
+    }
+
+    fn body(response: &HttpResponse) -> serde_json::Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    #[test]
+    fn route_fixtures() {
+        let mock = mock(MockOptions::default());
+        let response = mock.route("/quotes/latest?symbol=BTC%2CLTC&convert=EUR");
+        assert_eq!(response.status, 200);
+        assert!(body(&response)["data"]["BTC"]["quote"]["EUR"]["price"].is_number());
+        assert_eq!(mock.route("/quotes?convert=EUR").status, 200);
+
+        let response = mock.route("/pricemultifull?fsyms=BTC&tsyms=EUR&api_key=x");
+        assert_eq!(body(&response)["RAW"]["BTC"]["EUR"]["PRICE"], 3200.98);
+
+        assert_eq!(mock.route("/quotes/latest?convert=JPY").status, 404);
+        assert_eq!(mock.route("/map").status, 404);
+    }
+
+    #[test]
+    fn inject_failures() {
+        let failing = |failure| {
+            mock(MockOptions {
+                failure: Some(failure),
+                ..MockOptions::default()
+            })
+        };
+        let response = failing(Failure::RateLimit).route("/quotes/latest?convert=EUR");
+        assert_eq!(response.status, 429);
+        assert_eq!(body(&response)["status"]["error_code"], 429);
+        assert_eq!(failing(Failure::Unauthorized).route("/pricemultifull?tsyms=EUR").status, 401);
+        let response = failing(Failure::Malformed).route("/quotes/latest?convert=EUR");
+        assert_eq!(response.status, 200);
+        assert!(serde_json::from_str::<serde_json::Value>(&response.body).is_err());
+
+        let never = mock(MockOptions {
+            failure: Some(Failure::ServerError),
+            failure_rate: 0.0,
+            ..MockOptions::default()
+        });
+        assert_eq!(never.route("/quotes/latest?convert=EUR").status, 200);
+    }
+
+    #[test]
+    fn jitter() {
+        let mut value = json!({ "RAW": { "BTC": { "EUR": { "PRICE": 100.0, "MKTCAP": 100.0 } } } });
+        jitter_prices(&mut value, 5.0);
+        let price = value["RAW"]["BTC"]["EUR"]["PRICE"].as_f64().unwrap();
+        assert!((95.0..=105.0).contains(&price));
+        assert_eq!(value["RAW"]["BTC"]["EUR"]["MKTCAP"], 100.0);
+    }
+}
@@ -1,46 +1,98 @@
 pub mod api;
 pub mod config;
 pub mod constants;
+pub mod convert;
+pub mod decimal;
 pub mod env;
 mod errors;
+pub mod export;
+pub mod format;
+pub mod history;
 mod input;
+pub mod keys;
+pub mod metrics;
+pub mod mock_server;
+pub mod output;
+pub mod rest;
+mod server;
+pub mod signal;
+pub mod stream;
+pub mod theme;
 mod types;
+mod widgets;
 
 use self::{
     config::Config,
+    convert::{convert, ConversionQuery},
+    decimal::Decimal,
     errors::AppError,
     input::{InputChannel, InputEvent},
-    types::{AppResult, AppTerminal, Coins},
+    keys::{key_name, Action},
+    types::{AppFrame, AppResult, AppTerminal, Coins, PriceMove, SortOrder},
+    widgets::{column_at, sparkline_data, Clear, StyledTable, TableCell},
 };
 
-use termion::event::Key;
-use tui::{
-    style::{Color, Style},
-    widgets::{Table, Row},
-};
+use termion::event::{Key, MouseButton, MouseEvent};
 
 extern crate failure;
 extern crate termion;
 extern crate tui;
 
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
 use std::io;
+use std::time::{Duration, Instant};
+use termion::input::MouseTerminal;
 use termion::raw::IntoRawMode;
 use termion::screen::AlternateScreen;
 use tui::backend::TermionBackend;
-use tui::layout::{Alignment, Constraint, Direction, Layout};
-use tui::widgets::{Block, Borders, Paragraph, Text, Widget};
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use tui::widgets::{Block, Borders, Paragraph, Sparkline, Text, Widget};
 use tui::Terminal;
 
+// Widths of columns symbol, price (one per fiat) and 24h
+const SYMBOL_WIDTH: u16 = 10;
+const PRICE_WIDTH: u16 = 16;
+const CHANGE_WIDTH: u16 = 10;
+const COLUMN_SPACING: u16 = 1;
+// Periods (label, hours) of changes shown in detail view if history is enabled
+const HISTORY_PERIODS: [(&str, u64); 3] = [("1h", 1), ("7d", 7 * 24), ("30d", 30 * 24)];
+
+/// Changes and prices of a coin computed from history, which are queried once per refresh
+#[derive(Debug, Default)]
+struct HistoryStats {
+    /// Change of each period of `HISTORY_PERIODS`
+    changes: Vec<Option<Decimal>>,
+    /// Prices of the last 24 hours
+    prices: Vec<f64>,
+}
+
 pub struct App<'a> {
     config: Config<'a>,
     coins: Option<Coins>,
+    history_stats: HashMap<String, HistoryStats>,
     view_state: ViewState,
+    last_refresh: Instant,
+    price_flashes: HashMap<String, (PriceMove, Instant)>,
+    sort_order: SortOrder,
+    search: Option<String>,
+    /// Input of converter, e.g. `0.5 BTC EUR`, if it's shown
+    converter: Option<String>,
+    show_help: bool,
+    page_size: usize,
+    last_update: Option<DateTime<Local>>,
+    last_error: Option<String>,
+    credits_used: u32,
+    list_area: Rect,
+    list_offset: usize,
+    last_click: Option<(usize, Instant)>,
 }
 
 #[derive(Debug)]
 pub enum ViewState {
     Welcome,
     List,
+    Detail,
 }
 
 impl<'a> App<'a> {
@@ -48,12 +100,27 @@ impl<'a> App<'a> {
         App {
             config,
             coins: None,
+            history_stats: HashMap::new(),
             view_state: ViewState::Welcome,
+            last_refresh: Instant::now(),
+            price_flashes: HashMap::new(),
+            sort_order: SortOrder::Default,
+            search: None,
+            converter: None,
+            show_help: false,
+            page_size: 1,
+            last_update: None,
+            last_error: None,
+            credits_used: 0,
+            list_area: Rect::default(),
+            list_offset: 0,
+            last_click: None,
         }
     }
 
     fn init_terminal(&self) -> AppResult<AppTerminal> {
         let stdout = io::stdout().into_raw_mode().map_err(AppError::Terminal)?;
+        let stdout = MouseTerminal::from(stdout);
         let stdout = AlternateScreen::from(stdout);
         let backend = TermionBackend::new(stdout);
         let mut terminal = Terminal::new(backend).map_err(AppError::Terminal)?;
@@ -63,67 +130,54 @@ impl<'a> App<'a> {
 
     fn render(&mut self, terminal: &mut AppTerminal) -> AppResult<()> {
         let size = terminal.size().map_err(AppError::Terminal)?;
+        let rects = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Percentage(90),
+                    Constraint::Percentage(10),
+                ]
+                .as_ref(),
+            )
+            .margin(2)
+            .split(size);
+        // rows of coin table without its header
+        self.page_size = rects[0].height.saturating_sub(2).max(1) as usize;
+        self.list_area = rects[0];
+        // keep selected coin visible
+        self.list_offset = self
+            .coins
+            .as_ref()
+            .map_or(0, |coins| (coins.index + 1).saturating_sub(self.page_size));
+
+        let theme = &self.config.theme;
         terminal
             .draw(|mut f| {
                 Block::default()
                     .title("wtch-crpts")
+                    .title_style(theme.title())
+                    .border_style(theme.text())
                     .borders(Borders::ALL)
                     .render(&mut f, size);
 
-                let rects = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints(
-                        [
-                            Constraint::Percentage(90),
-                            Constraint::Percentage(10),
-                        ]
-                        .as_ref(),
-                    )
-                    .margin(2)
-                    .split(size);
-
                 let block = Block::default().borders(Borders::NONE);
                 match self.view_state {
                     ViewState::Welcome => {
                         // TODO: Create a factory to render a headline
-                        Paragraph::new(vec![Text::raw("Welcome")].iter())
+                        Paragraph::new([Text::styled("Welcome", theme.text())].iter())
                             .block(block)
                             .alignment(Alignment::Left)
                             .render(&mut f, rects[0]);
                     }
-                    ViewState::List => {
-                        if let Some(coins) = &self.coins {
-                            let mut rows = Vec::new();
-                            let normal_style =  Style::default().fg(Color::Black);
-                            let selected_style =  Style::default().fg(Color::Yellow);
-                            for coin in &coins.list {
-                                let quote = match &coin.quote {
-                                    None => "-".into(),
-                                    Some(q) => q.to_string()
-                                };
-                                let symbol = &coin.symbol;
-                                let row = vec![symbol.clone(), quote.clone()].into_iter();
-                                let style = match coins.current() {
-                                    Some(current) => if current.symbol == coin.symbol {
-                                        selected_style
-                                    } else {
-                                        normal_style
-                                    },
-                                    None => normal_style
-                                };
-                                rows.push(Row::StyledData(row, style));
-                            };
-
-                            Table::new(
-                                ["coin", "symbol", self.config.fiat_symbol].iter(),
-                                rows.into_iter()
-                            )
-                            .block(Block::default().borders(Borders::NONE))
-                            .column_spacing(1)
-                            .widths(&[10, 10, 10])
-                            .render(&mut f, rects[0]);
-                        }
-                    }
+                    ViewState::List => self.render_list(&mut f, rects[0]),
+                    ViewState::Detail => self.render_detail(&mut f, rects[0]),
+                }
+                self.render_status_bar(&mut f, rects[1]);
+                if let Some(input) = &self.converter {
+                    self.render_converter(&mut f, size, input);
+                }
+                if self.show_help {
+                    self.render_help(&mut f, size);
                 }
             })
             .map_err(AppError::Terminal)?;
@@ -131,36 +185,488 @@ impl<'a> App<'a> {
         Ok(())
     }
 
+    fn render_list(&self, f: &mut AppFrame, area: Rect) {
+        let theme = &self.config.theme;
+        let locale = &self.config.locale;
+        let mode = self.config.display_mode;
+        if let Some(coins) = &self.coins {
+            let mut rows = Vec::new();
+            let normal_style = theme.text();
+            let selected_style = theme.selected();
+            let current = coins.current();
+            for coin in &coins.list {
+                let style = match &current {
+                    Some(c) if c.symbol == coin.symbol => selected_style,
+                    _ => normal_style,
+                };
+                // prices are flashed, if the price in primary fiat has changed
+                let quote_style = self
+                    .price_flashes
+                    .get(&coin.symbol)
+                    .and_then(|(price_move, _)| theme.flash(*price_move))
+                    .unwrap_or(style);
+                let primary_fiat = self.config.primary_fiat();
+                let provided = coin.formatted(primary_fiat).and_then(|f| f.percent_change_24h.as_ref());
+                let (change, change_style) = match coin.percent_change_24h(primary_fiat) {
+                    None => ("-".into(), style),
+                    Some(c) => (mode.format(provided, || locale.change(c)), theme.change(style, c)),
+                };
+                let mut row = vec![TableCell::new(coin.symbol.clone(), style)];
+                for fiat in &self.config.fiat_symbols {
+                    let provided = coin.formatted(fiat).and_then(|f| f.price.as_ref());
+                    let quote = match coin.price(fiat) {
+                        None => "-".into(),
+                        Some(q) => mode.format(provided, || locale.price(q, fiat)),
+                    };
+                    row.push(TableCell::new(quote, quote_style));
+                }
+                row.push(TableCell::new(change, change_style));
+                rows.push(row);
+            }
+
+            let rows = rows.into_iter().skip(self.list_offset).collect();
+
+            let header_style = theme.header();
+            let mut header = vec![TableCell::new("symbol", header_style)];
+            header.extend(self.config.fiat_symbols.iter().map(|fiat| TableCell::new(*fiat, header_style)));
+            header.push(TableCell::new("24h", header_style));
+            let widths = self.column_widths();
+            StyledTable::new(header, rows)
+                .block(Block::default().borders(Borders::NONE))
+                .column_spacing(COLUMN_SPACING)
+                .widths(&widths)
+                .render(f, area);
+        }
+    }
+
+    /// Widths of columns of list: symbol, one price per fiat and 24h
+    fn column_widths(&self) -> Vec<u16> {
+        let mut widths = vec![SYMBOL_WIDTH];
+        widths.extend(self.config.fiat_symbols.iter().map(|_| PRICE_WIDTH));
+        widths.push(CHANGE_WIDTH);
+        widths
+    }
+
+    fn render_detail(&self, f: &mut AppFrame, area: Rect) {
+        let theme = &self.config.theme;
+        let locale = &self.config.locale;
+        let mode = self.config.display_mode;
+        if let Some(coin) = self.coins.as_ref().and_then(Coins::current) {
+            let value = |v: Option<String>| v.unwrap_or_else(|| "-".into());
+            let fiat = self.config.primary_fiat();
+            let mut lines = vec![("Symbol", coin.symbol.clone())];
+            for fiat in &self.config.fiat_symbols {
+                let provided = coin.formatted(fiat).and_then(|f| f.price.as_ref());
+                lines.push((fiat, value(coin.price(fiat).map(|p| mode.format(provided, || locale.price(p, fiat))))));
+            }
+            let derived: Vec<&str> = coin.quotes.values().filter_map(|q| q.derived_via.as_deref()).collect();
+            // values of primary fiat are shown as formatted by api provider, if display mode says so
+            let q = coin.quote(fiat).cloned().unwrap_or_default();
+            let provided = q.formatted.clone().unwrap_or_default();
+            let show = |provided: &Option<String>, local: Option<String>| {
+                value(local.map(|l| mode.format(provided.as_ref(), || l)))
+            };
+            lines.extend(vec![
+                ("24h", show(&provided.percent_change_24h, q.percent_change_24h.as_ref().map(|c| locale.change(c)))),
+                ("Market cap", show(&provided.market_cap, q.market_cap.as_ref().map(|m| locale.compact(m, fiat)))),
+                ("Volume 24h", show(&provided.volume_24h, q.volume_24h.as_ref().map(|v| locale.compact(v, fiat)))),
+                ("High 24h", show(&provided.high_24h, q.high_24h.as_ref().map(|p| locale.price(p, fiat)))),
+                ("Low 24h", show(&provided.low_24h, q.low_24h.as_ref().map(|p| locale.price(p, fiat)))),
+                ("Supply", show(&provided.supply, coin.supply.as_ref().map(|s| locale.number(s, 0)))),
+                ("Updated", value(q.last_updated.map(|t| t.format("%H:%M:%S").to_string()))),
+                ("Pricing", if derived.is_empty() { "direct".into() } else { derived.join(", ") }),
+            ]);
+            let mut prices: &[f64] = &[];
+            if self.config.history.is_some() {
+                // changes of other periods are computed from history, since not every api provider has them
+                let stats = self.history_stats.get(&coin.symbol);
+                for (i, (label, _)) in HISTORY_PERIODS.iter().enumerate() {
+                    let change = stats.and_then(|s| s.changes.get(i).cloned()).and_then(|c| c);
+                    lines.push((label, value(change.map(|c| locale.change(&c)))));
+                }
+                prices = stats.map_or(&[], |s| &s.prices);
+            }
+            let text: Vec<Text> = lines
+                .into_iter()
+                .flat_map(|(label, v)| {
+                    vec![
+                        Text::styled(format!("{:<12}", label), theme.header()),
+                        Text::styled(format!("{}\n", v), theme.text()),
+                    ]
+                })
+                .collect();
+            let rects = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(if prices.is_empty() { 0 } else { 8 })].as_ref())
+                .split(area);
+            Paragraph::new(text.iter())
+                .block(Block::default().borders(Borders::NONE))
+                .alignment(Alignment::Left)
+                .render(f, rects[0]);
+            if !prices.is_empty() {
+                let data = sparkline_data(prices, rects[1].width.saturating_sub(2) as usize);
+                Sparkline::default()
+                    .block(
+                        Block::default()
+                            .title("24h")
+                            .title_style(theme.title())
+                            .border_style(theme.text())
+                            .borders(Borders::ALL),
+                    )
+                    .data(&data)
+                    .max(100)
+                    .style(theme.text())
+                    .render(f, rects[1]);
+            }
+        }
+    }
+
+    /// Renders provider, fiat, refresh times, errors and used api credits
+    fn render_status_bar(&self, f: &mut AppFrame, area: Rect) {
+        let theme = &self.config.theme;
+        let mut text = Vec::new();
+        match &self.search {
+            Some(query) => text.push(Text::styled(format!("/{}", query), theme.text())),
+            None => {
+                let mut items = vec![self.config.api.get_name().to_string(), self.config.fiat_symbols.join(",")];
+                if let Some(updated) = self.last_update {
+                    items.push(format!("updated {}", updated.format("%H:%M:%S")));
+                }
+                if self.coins.as_ref().map(|c| c.stale).unwrap_or(false) {
+                    items.push("STALE".to_string());
+                }
+                let next_refresh = self.config.refresh_interval.checked_sub(self.last_refresh.elapsed());
+                items.push(format!("next in {}s", next_refresh.unwrap_or_default().as_secs()));
+                if self.config.api.capabilities().credits {
+                    items.push(format!("credits {}", self.credits_used));
+                }
+                if self.sort_order != SortOrder::Default {
+                    items.push(format!("sorted by {}", self.sort_order.label()));
+                }
+                if let Some(key) = self.config.keymap.keys(Action::Help).first() {
+                    items.push(format!("{} help", key_name(*key)));
+                }
+                text.push(Text::styled(items.join(" | "), theme.text()));
+            }
+        }
+        if let Some(error) = &self.last_error {
+            text.push(Text::styled(format!("\n{}", error), theme.error()));
+        }
+        Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::NONE))
+            .alignment(Alignment::Left)
+            .wrap(true)
+            .render(f, area);
+    }
+
+    /// Renders all key bindings as an overlay
+    fn render_help(&self, f: &mut AppFrame, size: Rect) {
+        let theme = &self.config.theme;
+        let help = self.config.keymap.help();
+        let key_width = help.iter().map(|(keys, _)| keys.len()).max().unwrap_or(0) + 2;
+        let text: Vec<Text> = help
+            .iter()
+            .flat_map(|(keys, description)| {
+                vec![
+                    Text::styled(format!("{:<1$}", keys, key_width), theme.header()),
+                    Text::styled(format!("{}\n", description), theme.text()),
+                ]
+            })
+            .collect();
+
+        let area = overlay_area(size, 64, help.len() as u16 + 2);
+        Clear.render(f, area);
+        Paragraph::new(text.iter())
+            .block(
+                Block::default()
+                    .title("Help")
+                    .title_style(theme.title())
+                    .border_style(theme.text())
+                    .borders(Borders::ALL),
+            )
+            .alignment(Alignment::Left)
+            .render(f, area);
+    }
+
+    /// Renders converter of amounts using current quotes as an overlay
+    fn render_converter(&self, f: &mut AppFrame, size: Rect, input: &str) {
+        let theme = &self.config.theme;
+        let coins = self.coins.as_ref().map(|c| c.list.as_slice()).unwrap_or(&[]);
+        let result = match input.parse::<ConversionQuery>().and_then(|q| convert(&q, coins)) {
+            Ok(conversion) => Text::styled(format!("{}\n", conversion), theme.header()),
+            Err(e) => Text::styled(format!("{}\n", e), theme.error()),
+        };
+        let text = [
+            Text::styled(format!("> {}\n", input), theme.text()),
+            result,
+            Text::styled("amount, from and to currency, e.g. 0.5 BTC EUR", theme.text()),
+        ];
+
+        let area = overlay_area(size, 64, text.len() as u16 + 2);
+        Clear.render(f, area);
+        Paragraph::new(text.iter())
+            .block(
+                Block::default()
+                    .title("Convert")
+                    .title_style(theme.title())
+                    .border_style(theme.text())
+                    .borders(Borders::ALL),
+            )
+            .alignment(Alignment::Left)
+            .render(f, area);
+    }
+
+    fn fetch_coins(&self) -> AppResult<Coins> {
+        self.config.fetch_coins()
+    }
+
+    /// Changes and prices of all coins in primary fiat stored in history by the current provider
+    fn query_history(&self, coins: &Coins) -> HashMap<String, HistoryStats> {
+        let history = match &self.config.history {
+            Some(history) => history,
+            None => return HashMap::new(),
+        };
+        let provider = self.config.api.get_name();
+        let fiat = self.config.primary_fiat();
+        let since = Local::now() - chrono::Duration::hours(24);
+        coins
+            .list
+            .iter()
+            .map(|coin| {
+                let changes = HISTORY_PERIODS
+                    .iter()
+                    .map(|(_, hours)| {
+                        let period = Duration::from_secs(hours * 60 * 60);
+                        history.change(provider, &coin.symbol, fiat, period).ok().and_then(|c| c)
+                    })
+                    .collect();
+                let prices = history
+                    .series(provider, &coin.symbol, fiat, &since)
+                    .map(|series| series.into_iter().map(|(_, price)| price).collect())
+                    .unwrap_or_default();
+                (coin.symbol.clone(), HistoryStats { changes, prices })
+            })
+            .collect()
+    }
+
+    /// Replaces coins and remembers changed prices to highlight them for a while
+    fn update_coins(&mut self, mut coins: Coins) {
+        coins.sort(self.sort_order, &self.config.crypto_symbols, self.config.primary_fiat());
+        if let Some(prev) = &self.coins {
+            let now = Instant::now();
+            for (symbol, price_move) in coins.price_moves(prev, self.config.primary_fiat()) {
+                self.price_flashes.insert(symbol, (price_move, now));
+            }
+            // keep selection
+            if let Some(current) = prev.current() {
+                coins.select_symbol(&current.symbol);
+            }
+        }
+        self.credits_used += coins.credit_count.unwrap_or(0);
+        self.last_update = Some(coins.cached_at.unwrap_or_else(Local::now));
+        self.last_error = None;
+        self.history_stats = self.query_history(&coins);
+        self.coins = Some(coins);
+    }
+
+    /// Fetches coins again. Previous coins are kept if it fails.
+    fn refresh(&mut self) {
+        self.last_refresh = Instant::now();
+        match self.fetch_coins() {
+            Ok(coins) => self.update_coins(coins),
+            Err(e) => {
+                warn!("refresh failed {}", e);
+                self.last_error = Some(format!("Refresh failed: {}", e));
+            }
+        }
+    }
+
+    fn on_tick(&mut self) {
+        let flash_duration = Duration::from_millis(constants::PRICE_FLASH_DURATION);
+        self.price_flashes.retain(|_, (_, at)| at.elapsed() < flash_duration);
+        if self.last_refresh.elapsed() >= self.config.refresh_interval {
+            self.refresh();
+        }
+    }
+
+    /// Handles a pressed key. Returns `false` to quit the app.
+    fn on_key(&mut self, key: Key) -> bool {
+        if let Some(input) = &mut self.converter {
+            match key {
+                Key::Esc | Key::Char('\n') => self.converter = None,
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Char(c) => input.push(c),
+                _ => {}
+            }
+            return true;
+        }
+        if let Some(query) = &mut self.search {
+            match key {
+                Key::Esc | Key::Char('\n') => self.search = None,
+                Key::Backspace => {
+                    query.pop();
+                }
+                Key::Char(c) => {
+                    query.push(c);
+                    let query = query.to_uppercase();
+                    if let Some(coins) = &mut self.coins {
+                        let found = coins.list.iter().find(|c| c.symbol.to_uppercase().starts_with(&query));
+                        if let Some(symbol) = found.map(|c| c.symbol.clone()) {
+                            coins.select_symbol(&symbol);
+                        }
+                    }
+                }
+                _ => {}
+            }
+            return true;
+        }
+        if key == Key::Esc {
+            self.show_help = false;
+            if let ViewState::Detail = self.view_state {
+                self.view_state = ViewState::List;
+            }
+            return true;
+        }
+        match self.config.keymap.action(key) {
+            Some(action) => self.on_action(action),
+            None => true,
+        }
+    }
+
+    /// Handles an action. Returns `false` to quit the app.
+    fn on_action(&mut self, action: Action) -> bool {
+        match action {
+            Action::Quit => return false,
+            Action::Help => self.show_help = !self.show_help,
+            Action::Refresh => self.refresh(),
+            Action::Search => self.search = Some(String::new()),
+            Action::Convert => {
+                // starts with an amount of selected coin in primary fiat
+                let symbol = self.coins.as_ref().and_then(Coins::current).map(|c| c.symbol);
+                let input = format!("1 {} {}", symbol.as_deref().unwrap_or("BTC"), self.config.primary_fiat());
+                self.converter = Some(input);
+            }
+            Action::Detail => {
+                self.view_state = match self.view_state {
+                    ViewState::Detail => ViewState::List,
+                    _ => ViewState::Detail,
+                }
+            }
+            Action::Sort => self.sort(self.sort_order.next()),
+            Action::Up | Action::Down | Action::PageUp | Action::PageDown | Action::Top | Action::Bottom => {
+                if let Some(coins) = &mut self.coins {
+                    let index = coins.index;
+                    match action {
+                        Action::Up => coins.prev(),
+                        Action::Down => coins.next(),
+                        Action::PageUp => coins.select(index.saturating_sub(self.page_size)),
+                        Action::PageDown => coins.select(index + self.page_size),
+                        Action::Top => coins.select(0),
+                        _ => coins.select(coins.list.len().saturating_sub(1)),
+                    };
+                }
+            }
+        }
+        true
+    }
+
+    fn sort(&mut self, order: SortOrder) {
+        self.sort_order = order;
+        if let Some(coins) = &mut self.coins {
+            coins.sort(order, &self.config.crypto_symbols, self.config.primary_fiat());
+        }
+    }
+
+    /// Handles mouse events: Selecting rows by click, showing details by double click,
+    /// scrolling by wheel and sorting by clicking column headers
+    fn on_mouse(&mut self, event: MouseEvent) {
+        let (button, x, y) = match event {
+            // termion coordinates are one-based
+            MouseEvent::Press(button, x, y) => (button, x.saturating_sub(1), y.saturating_sub(1)),
+            _ => return,
+        };
+        match button {
+            MouseButton::WheelUp => {
+                self.on_action(Action::Up);
+            }
+            MouseButton::WheelDown => {
+                self.on_action(Action::Down);
+            }
+            MouseButton::Left => {
+                if self.show_help {
+                    self.show_help = false;
+                    return;
+                }
+                if let ViewState::List = self.view_state {
+                    self.on_list_click(x, y);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_list_click(&mut self, x: u16, y: u16) {
+        let area = self.list_area;
+        if x < area.left() || x >= area.right() || y < area.top() || y >= area.bottom() {
+            return;
+        }
+        if y == area.top() {
+            let widths = self.column_widths();
+            // all price columns are sorted by price in primary fiat
+            let order = match column_at(&widths, COLUMN_SPACING, x - area.left()) {
+                Some(0) => SortOrder::Symbol,
+                Some(i) if i == widths.len() - 1 => SortOrder::Change,
+                Some(_) => SortOrder::Price,
+                None => return,
+            };
+            // clicking the header of current sort column again resets the order
+            self.sort(if order == self.sort_order { SortOrder::Default } else { order });
+            return;
+        }
+        // below header and its spacing line
+        if y < area.top() + 2 {
+            return;
+        }
+        let index = self.list_offset + (y - area.top() - 2) as usize;
+        if let Some(coins) = &mut self.coins {
+            if index >= coins.list.len() {
+                return;
+            }
+            coins.select(index);
+        }
+        let double_click = Duration::from_millis(constants::DOUBLE_CLICK_DURATION);
+        match self.last_click {
+            Some((last_index, at)) if last_index == index && at.elapsed() < double_click => {
+                self.last_click = None;
+                self.view_state = ViewState::Detail;
+            }
+            _ => self.last_click = Some((index, Instant::now())),
+        }
+    }
+
     pub fn run(&mut self) -> AppResult<()> {
         let mut terminal = self.init_terminal()?;
         self.view_state = ViewState::List;
         self.render(&mut terminal)?;
-        let coins = self.config.api.get_coin_details(&self.config.crypto_symbols, &self.config.fiat_symbol)?;
-        self.coins = Some(coins);
-        // info!("{:?}", self);
+        let coins = self.fetch_coins()?;
+        self.update_coins(coins);
+        self.last_refresh = Instant::now();
 
-        let inp_channel = InputChannel::new();
+        let inp_channel = InputChannel::new(Duration::from_millis(constants::TICK_RATE));
 
         loop {
             self.render(&mut terminal)?;
             match inp_channel.rx.recv() {
                 Ok(inp_event) => match inp_event {
-                    InputEvent::Exit => {
-                        break;
-                    }
-                    InputEvent::InputKey(key) => match key {
-                        Key::Up => {
-                            if let Some(coins) = &mut self.coins {
-                                coins.next();
-                            }
-                        }
-                        Key::Down => {
-                            if let Some(coins) = &mut self.coins {
-                                coins.prev();
-                            }
+                    InputEvent::InputKey(key) => {
+                        if !self.on_key(key) {
+                            break;
                         }
-                        _ => {}
-                    },
+                    }
+                    InputEvent::InputMouse(mouse) => self.on_mouse(mouse),
+                    InputEvent::Tick => self.on_tick(),
                 },
                 Err(_) => eprintln!("Error to get InputEvent"),
             }
@@ -168,3 +674,15 @@ impl<'a> App<'a> {
         Ok(())
     }
 }
+
+/// Area of an overlay of given size centered in `size`
+fn overlay_area(size: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(size.width);
+    let height = height.min(size.height);
+    Rect::new(
+        size.x + (size.width - width) / 2,
+        size.y + (size.height - height) / 2,
+        width,
+        height,
+    )
+}
@@ -0,0 +1,313 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use super::{
+    api::Capabilities,
+    config::Config,
+    decimal::Decimal,
+    errors::AppError,
+    types::{AppResult, Coin, Coins},
+};
+
+// Formats supported to print coins
+pub const OUTPUT_FORMATS: [&str; 4] = ["table", "json", "csv", "tsv"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+    Tsv,
+}
+
+impl FromStr for OutputFormat {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(AppError::OutputFormat { name: s.into() }),
+        }
+    }
+}
+
+// Names of fields of a record, e.g. header of csv
+const RECORD_FIELDS: [&str; 5] = ["symbol", "fiat", "price", "percent_change_24h", "market_cap"];
+
+// Header of table of api providers
+const PROVIDER_FIELDS: [&str; 9] = [
+    "provider",
+    "api key",
+    "fiats",
+    "crypto quotes",
+    "multiple fiats",
+    "history",
+    "listings",
+    "streaming",
+    "credits",
+];
+
+/// Single line of output
+#[derive(Serialize, Debug, PartialEq)]
+pub(super) struct Record<'a> {
+    symbol: &'a str,
+    fiat: &'a str,
+    price: Option<&'a Decimal>,
+    percent_change_24h: Option<&'a Decimal>,
+    market_cap: Option<&'a Decimal>,
+}
+
+impl<'a> Record<'a> {
+    pub(super) fn new(coin: &'a Coin, fiat: &'a str) -> Self {
+        let quote = coin.quote(fiat);
+        Record {
+            symbol: &coin.symbol,
+            fiat,
+            price: quote.and_then(|q| q.price.as_ref()),
+            percent_change_24h: quote.and_then(|q| q.percent_change_24h.as_ref()),
+            market_cap: quote.and_then(|q| q.market_cap.as_ref()),
+        }
+    }
+
+    /// Values of all fields in order of `RECORD_FIELDS`. Missing values are empty.
+    fn fields(&self) -> Vec<String> {
+        let value = |v: Option<&Decimal>| v.map(Decimal::to_string).unwrap_or_default();
+        vec![
+            self.symbol.to_string(),
+            self.fiat.to_string(),
+            value(self.price),
+            value(self.percent_change_24h),
+            value(self.market_cap),
+        ]
+    }
+
+    /// Records of a coin in all given fiats
+    pub(super) fn all(coin: &'a Coin, fiats: &[&'a str]) -> Vec<Self> {
+        fiats.iter().map(|fiat| Record::new(coin, fiat)).collect()
+    }
+}
+
+/// Fetches coins once and prints them. Returns all symbols, which could not be resolved by the api provider.
+pub fn print_coins<W: Write>(config: &Config, format: OutputFormat, out: W) -> AppResult<Vec<String>> {
+    let coins = config.fetch_coins()?;
+    warn_if_stale(&coins);
+    write_coins(&coins, &config.fiat_symbols, format, out)?;
+    Ok(unresolved_symbols(&coins, &config.crypto_symbols))
+}
+
+/// Writes a warning to stderr, if coins are served from an outdated cache
+pub fn warn_if_stale(coins: &Coins) {
+    if let (true, Some(cached_at)) = (coins.stale, coins.cached_at) {
+        eprintln!("Data is stale, it's cached since {}", cached_at.format("%Y-%m-%d %H:%M:%S"));
+    }
+}
+
+pub fn unresolved_symbols(coins: &Coins, symbols: &[&str]) -> Vec<String> {
+    symbols
+        .iter()
+        .filter(|s| !coins.list.iter().any(|c| c.symbol == **s))
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Writes one record per coin and fiat
+pub fn write_coins<W: Write>(coins: &Coins, fiats: &[&str], format: OutputFormat, mut out: W) -> AppResult<()> {
+    let records: Vec<Record> = coins.list.iter().flat_map(|c| Record::all(c, fiats)).collect();
+    match format {
+        OutputFormat::Table => write_table(&records, &mut out).map_err(AppError::Output)?,
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, &records)?;
+            writeln!(out).map_err(AppError::Output)?;
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let delimiter = if format == OutputFormat::Csv { b',' } else { b'\t' };
+            let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(out);
+            // decimals are serialized as json numbers, so records are written as text
+            writer.write_record(RECORD_FIELDS).map_err(AppError::Csv)?;
+            for record in &records {
+                writer.write_record(record.fields()).map_err(AppError::Csv)?;
+            }
+            writer.flush().map_err(AppError::Output)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes a table of api providers by name and their capabilities
+pub fn write_providers<W: Write>(providers: &[(&str, Capabilities)], mut out: W) -> AppResult<()> {
+    let flag = |supported: bool| if supported { "yes" } else { "no" }.to_string();
+    let mut rows = vec![PROVIDER_FIELDS.iter().map(|h| h.to_string()).collect()];
+    for (name, c) in providers {
+        rows.push(vec![
+            name.to_string(),
+            c.key_env.unwrap_or("-").to_string(),
+            c.fiats.len().to_string(),
+            flag(c.crypto_quotes),
+            flag(c.multiple_fiats),
+            flag(c.history),
+            flag(c.listings),
+            flag(c.streaming),
+            flag(c.credits),
+        ]);
+    }
+    write_rows(&rows, 2, &mut out).map_err(AppError::Output)
+}
+
+/// Writes records as a text table with aligned columns
+fn write_table<W: Write>(records: &[Record], out: &mut W) -> std::io::Result<()> {
+    let value = |v: Option<String>| v.unwrap_or_else(|| "-".into());
+    let mut rows = vec![vec![
+        "symbol".to_string(),
+        "fiat".to_string(),
+        "price".to_string(),
+        "24h".to_string(),
+        "market cap".to_string(),
+    ]];
+    for r in records {
+        rows.push(vec![
+            r.symbol.to_string(),
+            r.fiat.to_string(),
+            value(r.price.map(|p| p.to_string())),
+            value(r.percent_change_24h.map(|c| format!("{:+.2}%", c))),
+            value(r.market_cap.map(|m| format!("{:.0}", m))),
+        ]);
+    }
+    // symbols are aligned left, all numbers right
+    write_rows(&rows, 2, out)
+}
+
+/// Writes rows with aligned columns. First `left_columns` are aligned left, others right.
+fn write_rows<W: Write>(rows: &[Vec<String>], left_columns: usize, out: &mut W) -> std::io::Result<()> {
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| row[i].chars().count()).max().unwrap_or(0))
+        .collect();
+    for row in rows {
+        let line: Vec<String> = row
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| {
+                if i < left_columns {
+                    format!("{:<1$}", cell, widths[i])
+                } else {
+                    format!("{:>1$}", cell, widths[i])
+                }
+            })
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::api::{coinmarketcap::CoinMarketCap, cryptocompare::CryptoCompare, Api};
+    use crate::app::types::{CoinBuilder, QuoteBuilder};
+
+    fn coins() -> Coins {
+        Coins::new(vec![
+            CoinBuilder::default()
+                .symbol("BTC")
+                .quote(
+                    "EUR",
+                    QuoteBuilder::default()
+                        .price(Decimal::from(3200.5))
+                        .percent_change_24h(Decimal::from(-0.8))
+                        .market_cap(Decimal::from(56_110_256_905.0))
+                        .build()
+                        .unwrap(),
+                )
+                .price("USD", 3650.25)
+                .build()
+                .unwrap(),
+            CoinBuilder::default().symbol("LTC").price("EUR", 37.25).build().unwrap(),
+        ])
+    }
+
+    fn output_fiats(format: OutputFormat, fiats: &[&str]) -> String {
+        let mut out = Vec::new();
+        write_coins(&coins(), fiats, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn output(format: OutputFormat) -> String {
+        output_fiats(format, &["EUR"])
+    }
+
+    #[test]
+    fn output_table() {
+        let expected = "\
+symbol  fiat   price     24h   market cap
+BTC     EUR   3200.5  -0.80%  56110256905
+LTC     EUR    37.25       -            -
+";
+        assert_eq!(output(OutputFormat::Table), expected);
+    }
+
+    #[test]
+    fn output_fiats_table() {
+        let expected = "\
+symbol  fiat    price     24h   market cap
+BTC     EUR    3200.5  -0.80%  56110256905
+BTC     USD   3650.25       -            -
+LTC     EUR     37.25       -            -
+LTC     USD         -       -            -
+";
+        assert_eq!(output_fiats(OutputFormat::Table, &["EUR", "USD"]), expected);
+    }
+
+    #[test]
+    fn output_csv() {
+        let expected = "\
+symbol,fiat,price,percent_change_24h,market_cap
+BTC,EUR,3200.5,-0.8,56110256905
+LTC,EUR,37.25,,
+";
+        assert_eq!(output(OutputFormat::Csv), expected);
+        assert_eq!(output(OutputFormat::Tsv), expected.replace(',', "\t"));
+    }
+
+    #[test]
+    fn output_json() {
+        let result: serde_json::Value = serde_json::from_str(&output(OutputFormat::Json)).unwrap();
+        assert_eq!(result[0]["symbol"], "BTC");
+        assert_eq!(result[0]["fiat"], "EUR");
+        assert_eq!(result[0]["price"], 3200.5);
+        assert_eq!(result[1]["percent_change_24h"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn unresolved() {
+        assert_eq!(unresolved_symbols(&coins(), &["BTC", "XYZ", "LTC"]), vec!["XYZ"]);
+    }
+    #[test]
+    fn output_exact_decimals() {
+        let price: Decimal = "98765432109876543210.123456789".parse().unwrap();
+        let coins = Coins::new(vec![CoinBuilder::default().symbol("SHIB").price("IRR", price).build().unwrap()]);
+        let write = |format: OutputFormat| {
+            let mut out = Vec::new();
+            write_coins(&coins, &["IRR"], format, &mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert!(write(OutputFormat::Json).contains("\"price\": 98765432109876543210.123456789,"));
+        assert!(write(OutputFormat::Csv).contains("SHIB,IRR,98765432109876543210.123456789,,"));
+        assert!(write(OutputFormat::Table).contains("98765432109876543210.123456789"));
+    }
+
+    #[test]
+    fn output_providers() {
+        let (cmc, cc) = (CoinMarketCap::new(None), CryptoCompare::new(None));
+        let mut out = Vec::new();
+        write_providers(&[(cmc.get_name(), cmc.capabilities()), (cc.get_name(), cc.capabilities())], &mut out).unwrap();
+        let expected = "\
+provider       api key            fiats  crypto quotes  multiple fiats  history  listings  streaming  credits
+coinmarketcap  COINMARKETCAP_KEY     93             no             yes       no        no         no      yes
+cryptocompare  CRYPTOCOMPARE_KEY     36            yes             yes       no        no         no       no
+";
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+}
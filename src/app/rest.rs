@@ -0,0 +1,225 @@
+use chrono::{DateTime, Local};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::{
+    config::Config,
+    output::Record,
+    server::{self, HttpResponse},
+    signal::sleep_while_running,
+    types::{AppResult, Coins},
+};
+
+// Address to serve coins if no other address is given
+pub const DEFAULT_SERVE_ADDR: &str = "127.0.0.1:9185";
+
+/// Latest coins fetched from api provider, shared by all clients
+#[derive(Debug, Default)]
+pub struct Cache {
+    provider: String,
+    fiats: Vec<String>,
+    coins: Option<Coins>,
+    updated: Option<DateTime<Local>>,
+    last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CoinsResponse<'a> {
+    provider: &'a str,
+    updated: String,
+    coins: Vec<Record<'a>>,
+}
+
+#[derive(Serialize)]
+struct HealthResponse<'a> {
+    status: &'a str,
+    provider: &'a str,
+    updated: Option<String>,
+    error: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse<'a> {
+    error: &'a str,
+}
+
+impl Cache {
+    pub fn new(provider: &str, fiats: &[&str]) -> Self {
+        Cache {
+            provider: provider.into(),
+            fiats: fiats.iter().map(|f| f.to_string()).collect(),
+            ..Cache::default()
+        }
+    }
+
+    pub fn update(&mut self, result: AppResult<Coins>) {
+        match result {
+            Ok(coins) => {
+                self.coins = Some(coins);
+                self.updated = Some(Local::now());
+                self.last_error = None;
+            }
+            Err(e) => {
+                warn!("failed to get data {}", e);
+                self.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Response to a request of given url. `/coins` lists coins in all fiats, `/coins/{symbol}` in the first one.
+    pub fn route(&self, url: &str) -> HttpResponse {
+        // query strings are ignored
+        let path = url.split('?').next().unwrap_or("").trim_end_matches('/');
+        let fiats: Vec<&str> = self.fiats.iter().map(String::as_str).collect();
+        match path {
+            "/health" => self.health(),
+            "/coins" => self.with_coins(|coins, updated| {
+                let records = coins.list.iter().flat_map(|c| Record::all(c, &fiats)).collect();
+                json(
+                    200,
+                    &CoinsResponse {
+                        provider: &self.provider,
+                        updated: updated.to_rfc3339(),
+                        coins: records,
+                    },
+                )
+            }),
+            _ => match path.strip_prefix("/coins/") {
+                Some(symbol) if !symbol.contains('/') => self.with_coins(|coins, _| {
+                    match coins.list.iter().find(|c| c.symbol.eq_ignore_ascii_case(symbol)) {
+                        Some(coin) => json(200, &Record::new(coin, fiats[0])),
+                        None => error(404, &format!("Unknown symbol {}", symbol)),
+                    }
+                }),
+                _ => error(404, "Not found"),
+            },
+        }
+    }
+
+    fn with_coins<F>(&self, f: F) -> HttpResponse
+    where
+        F: FnOnce(&Coins, &DateTime<Local>) -> HttpResponse,
+    {
+        match (&self.coins, &self.updated) {
+            (Some(coins), Some(updated)) => f(coins, updated),
+            _ => error(503, "No data available yet"),
+        }
+    }
+
+    fn health(&self) -> HttpResponse {
+        // data is still served if the latest request failed, so only missing data is unhealthy
+        let (status, code) = match (&self.coins, &self.last_error) {
+            (None, _) => ("unavailable", 503),
+            (Some(_), Some(_)) => ("degraded", 200),
+            (Some(_), None) => ("ok", 200),
+        };
+        json(
+            code,
+            &HealthResponse {
+                status,
+                provider: &self.provider,
+                updated: self.updated.map(|u| u.to_rfc3339()),
+                error: self.last_error.as_deref(),
+            },
+        )
+    }
+}
+
+fn json<T: serde::Serialize>(status: u16, body: &T) -> HttpResponse {
+    match serde_json::to_string(body) {
+        Ok(body) => HttpResponse::json(status, body),
+        Err(e) => HttpResponse::new(500, "text/plain", e.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> HttpResponse {
+    json(status, &ErrorResponse { error: message })
+}
+
+/// Serves coins as JSON and refreshes them in an interval until `running` is set to `false`.
+/// All clients share the latest coins, so the api provider is requested once per interval.
+pub fn serve_coins(config: &Config, addr: &str, running: Arc<AtomicBool>) -> AppResult<()> {
+    let cache = Arc::new(Mutex::new(Cache::new(config.api.get_name(), &config.fiat_symbols)));
+    {
+        let cache = cache.clone();
+        server::spawn(addr, move |url| match cache.lock() {
+            Ok(cache) => cache.route(url),
+            Err(_) => error(500, "Cache is not available"),
+        })?;
+    }
+
+    while running.load(Ordering::SeqCst) {
+        let started = Instant::now();
+        let result = config.fetch_coins();
+        if let Ok(mut cache) = cache.lock() {
+            cache.update(result);
+        }
+        sleep_while_running(&running, started, config.refresh_interval);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::errors::AppError;
+    use crate::app::types::CoinBuilder;
+
+    fn cache() -> Cache {
+        let mut cache = Cache::new("coinmarketcap", &["EUR", "USD"]);
+        cache.update(Ok(Coins::new(vec![
+            CoinBuilder::default().symbol("BTC").price("EUR", 3200.5).price("USD", 3650.25).build().unwrap(),
+            CoinBuilder::default().symbol("LTC").price("EUR", 37.25).build().unwrap(),
+        ])));
+        cache
+    }
+
+    fn body(response: &HttpResponse) -> serde_json::Value {
+        serde_json::from_str(&response.body).unwrap()
+    }
+
+    #[test]
+    fn route_coins() {
+        let response = cache().route("/coins?fiat=USD");
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "application/json");
+        let result = body(&response);
+        assert_eq!(result["provider"], "coinmarketcap");
+        assert_eq!(result["coins"][0]["symbol"], "BTC");
+        assert_eq!(result["coins"][1]["price"], 3650.25);
+        assert_eq!(result["coins"][1]["fiat"], "USD");
+        assert_eq!(result["coins"][2]["price"], 37.25);
+        assert_eq!(result["coins"][2]["fiat"], "EUR");
+    }
+
+    #[test]
+    fn route_coin() {
+        let cache = cache();
+        let response = cache.route("/coins/ltc");
+        assert_eq!(response.status, 200);
+        assert_eq!(body(&response)["symbol"], "LTC");
+        assert_eq!(body(&response)["fiat"], "EUR");
+        assert_eq!(cache.route("/coins/XYZ").status, 404);
+        assert_eq!(cache.route("/coins/BTC/x").status, 404);
+        assert_eq!(cache.route("/prices").status, 404);
+    }
+
+    #[test]
+    fn route_health() {
+        let mut cache = Cache::new("coinmarketcap", &["EUR"]);
+        assert_eq!(cache.route("/coins").status, 503);
+        assert_eq!(cache.route("/health").status, 503);
+        assert_eq!(body(&cache.route("/health"))["status"], "unavailable");
+
+        cache.update(Ok(Coins::new(vec![])));
+        assert_eq!(body(&cache.route("/health"))["status"], "ok");
+
+        cache.update(Err(AppError::ApiParseMap { key: "x".into() }));
+        let response = cache.route("/health");
+        assert_eq!(response.status, 200);
+        assert_eq!(body(&response)["status"], "degraded");
+        assert_eq!(cache.route("/coins").status, 200);
+    }
+}
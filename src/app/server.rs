@@ -0,0 +1,64 @@
+use std::thread;
+use tiny_http::{Header, Method, Response, Server};
+
+use super::{errors::AppError, types::AppResult};
+
+/// Response of a route handled by a http server
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl HttpResponse {
+    pub fn new<S: Into<String>>(status: u16, content_type: &'static str, body: S) -> Self {
+        HttpResponse {
+            status,
+            content_type,
+            body: body.into(),
+        }
+    }
+
+    pub fn json<S: Into<String>>(status: u16, body: S) -> Self {
+        HttpResponse::new(status, "application/json", body)
+    }
+
+    pub fn not_found() -> Self {
+        HttpResponse::new(404, "text/plain", "Not found")
+    }
+}
+
+/// Starts a http server in a separate thread. All `GET` requests are handled by given handler,
+/// which gets the requested url (path and query). Other methods are rejected.
+pub fn spawn<F>(addr: &str, handler: F) -> AppResult<thread::JoinHandle<()>>
+where
+    F: Fn(&str) -> HttpResponse + Send + 'static,
+{
+    let server = Server::http(addr).map_err(|e| AppError::Server {
+        addr: addr.into(),
+        reason: e.to_string(),
+    })?;
+    info!("listening on {}", addr);
+    let handle = thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = if *request.method() == Method::Get {
+                handler(request.url())
+            } else {
+                HttpResponse::new(405, "text/plain", "Method not allowed")
+            };
+            info!("{} {} {}", request.method(), request.url(), response.status);
+            let content_type = Header::from_bytes(&b"Content-Type"[..], response.content_type.as_bytes())
+                .expect("content type is a valid header");
+            let result = request.respond(
+                Response::from_data(response.body.into_bytes())
+                    .with_status_code(response.status)
+                    .with_header(content_type),
+            );
+            if let Err(e) = result {
+                warn!("failed to respond {}", e);
+            }
+        }
+    });
+    Ok(handle)
+}
@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{errors::AppError, types::AppResult};
+
+/// Flag, which is set to `false` by SIGINT (e.g. `Ctrl+C`)
+pub fn stop_on_interrupt() -> AppResult<Arc<AtomicBool>> {
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || r.store(false, Ordering::SeqCst)).map_err(AppError::Signal)?;
+    Ok(running)
+}
+
+/// Sleeps until `interval` has been elapsed since `started` or `running` is set to `false`
+pub fn sleep_while_running(running: &AtomicBool, started: Instant, interval: Duration) {
+    sleep_while_open(running, started, interval, || false);
+}
+
+/// Sleeps like `sleep_while_running`, but stops as soon as `closed` returns `true`, which is returned then
+pub fn sleep_while_open<F: Fn() -> bool>(running: &AtomicBool, started: Instant, interval: Duration, closed: F) -> bool {
+    // sleep in small steps to stop quickly
+    while running.load(Ordering::SeqCst) && started.elapsed() < interval {
+        if closed() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    false
+}
+
+/// Whether stdout can't be written anymore, e.g. because the reader of a pipe (like `head`) exited.
+/// It's detected without writing, which is only supported on unix.
+#[cfg(unix)]
+pub fn is_stdout_closed() -> bool {
+    let mut fd = libc::pollfd {
+        fd: libc::STDOUT_FILENO,
+        events: 0,
+        revents: 0,
+    };
+    // errors and hangups are reported even if no events are requested
+    let ready = unsafe { libc::poll(&mut fd, 1, 0) };
+    ready > 0 && fd.revents & (libc::POLLERR | libc::POLLHUP) != 0
+}
+
+#[cfg(not(unix))]
+pub fn is_stdout_closed() -> bool {
+    false
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Local};
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use super::{
+    config::Config,
+    decimal::Decimal,
+    errors::AppError,
+    output::{warn_if_stale, Record},
+    signal::sleep_while_open,
+    types::{AppResult, Coin},
+};
+
+// Template used to stream lines if no other template is given
+pub const DEFAULT_TEMPLATE: &str = "{time} {symbol} {price} {change_24h:+.2}%";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Time,
+    Symbol,
+    Fiat,
+    Price,
+    Change24h,
+    MarketCap,
+}
+
+impl FromStr for Field {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "time" => Ok(Field::Time),
+            "symbol" => Ok(Field::Symbol),
+            "fiat" => Ok(Field::Fiat),
+            "price" => Ok(Field::Price),
+            "change_24h" => Ok(Field::Change24h),
+            "market_cap" => Ok(Field::MarketCap),
+            _ => Err(AppError::Template {
+                reason: format!("unknown field {}", s),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Field {
+        field: Field,
+        sign: bool,
+        precision: Option<usize>,
+    },
+}
+
+/// Template of a line, e.g. `{symbol} {price:.2} {change_24h:+.1}%`
+///
+/// Fields are `time`, `symbol`, `fiat`, `price`, `change_24h` and `market_cap`.
+/// Numbers can be formatted with a leading sign (`+`) and a precision (`.2`).
+/// Braces are escaped by doubling them (`{{`, `}}`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl FromStr for Template {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |reason: &str| AppError::Template { reason: reason.into() };
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '}' => return Err(error("unmatched '}'")),
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(error("unmatched '{'")),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(literal.clone()));
+                        literal.clear();
+                    }
+                    parts.push(parse_placeholder(&placeholder)?);
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Template { parts })
+    }
+}
+
+/// Parses content of a placeholder, e.g. `change_24h:+.1`
+fn parse_placeholder(placeholder: &str) -> AppResult<Part> {
+    let mut split = placeholder.splitn(2, ':');
+    let field: Field = split.next().unwrap_or("").trim().parse()?;
+    let spec = split.next().unwrap_or("");
+    let (sign, spec) = match spec.strip_prefix('+') {
+        Some(rest) => (true, rest),
+        None => (false, spec),
+    };
+    let precision = match spec {
+        "" => None,
+        _ => {
+            let digits = spec.strip_prefix('.').ok_or_else(|| AppError::Template {
+                reason: format!("invalid format {}", placeholder),
+            })?;
+            Some(digits.parse().map_err(|_| AppError::Template {
+                reason: format!("invalid precision {}", placeholder),
+            })?)
+        }
+    };
+    Ok(Part::Field { field, sign, precision })
+}
+
+impl Template {
+    pub fn render(&self, coin: &Coin, fiat: &str, time: &DateTime<Local>) -> String {
+        let quote = coin.quote(fiat);
+        let number = |value: Option<&Decimal>, sign: bool, precision: Option<usize>| match (value, precision) {
+            (None, _) => "-".to_string(),
+            (Some(v), Some(p)) if sign => format!("{:+.*}", p, v),
+            (Some(v), Some(p)) => format!("{:.*}", p, v),
+            (Some(v), None) if sign => format!("{:+}", v),
+            (Some(v), None) => v.to_string(),
+        };
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(l) => l.clone(),
+                Part::Field { field, sign, precision } => match field {
+                    Field::Time => time.to_rfc3339(),
+                    Field::Symbol => coin.symbol.clone(),
+                    Field::Fiat => fiat.to_string(),
+                    Field::Price => number(quote.and_then(|q| q.price.as_ref()), *sign, *precision),
+                    Field::Change24h => number(quote.and_then(|q| q.percent_change_24h.as_ref()), *sign, *precision),
+                    Field::MarketCap => number(quote.and_then(|q| q.market_cap.as_ref()), *sign, *precision),
+                },
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamFormat {
+    Line(Template),
+    Json,
+}
+
+#[derive(Serialize)]
+struct StreamRecord<'a> {
+    time: String,
+    #[serde(flatten)]
+    record: Record<'a>,
+}
+
+/// Fetches coins in an interval and writes one line per coin and fiat until `running` is set to `false`
+/// or output is closed (e.g. by a closed pipe), which is checked by `closed` while waiting for the next refresh.
+/// Errors of api provider are written to stderr without stopping the stream.
+pub fn stream_coins<W: Write, F: Fn() -> bool>(
+    config: &Config,
+    format: &StreamFormat,
+    mut out: W,
+    running: Arc<AtomicBool>,
+    closed: F,
+) -> AppResult<()> {
+    while running.load(Ordering::SeqCst) {
+        let started = Instant::now();
+        match config.fetch_coins() {
+            Ok(coins) => {
+                warn_if_stale(&coins);
+                let time = Local::now();
+                let result = coins
+                    .list
+                    .iter()
+                    .flat_map(|coin| config.fiat_symbols.iter().map(move |fiat| (coin, *fiat)))
+                    .try_for_each(|(coin, fiat)| write_coin(&mut out, coin, fiat, format, &time))
+                    .and_then(|_| out.flush());
+                match result {
+                    Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+                    Err(e) => return Err(AppError::Output(e)),
+                    Ok(_) => {}
+                }
+            }
+            Err(e) => eprintln!("Failed to get data: {}", e),
+        }
+        if sleep_while_open(&running, started, config.refresh_interval, &closed) {
+            return Ok(());
+        }
+    }
+    Ok(())
+}
+
+fn write_coin<W: Write>(out: &mut W, coin: &Coin, fiat: &str, format: &StreamFormat, time: &DateTime<Local>) -> io::Result<()> {
+    match format {
+        StreamFormat::Line(template) => writeln!(out, "{}", template.render(coin, fiat, time)),
+        StreamFormat::Json => {
+            let record = StreamRecord {
+                time: time.to_rfc3339(),
+                record: Record::new(coin, fiat),
+            };
+            serde_json::to_writer(&mut *out, &record)?;
+            writeln!(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::app::types::{CoinBuilder, QuoteBuilder};
+    use chrono::TimeZone;
+
+    fn coin() -> Coin {
+        let quote = QuoteBuilder::default()
+            .price(Decimal::from(3200.98))
+            .percent_change_24h(Decimal::from(-0.797))
+            .market_cap(Decimal::from(56_110_256_905.26))
+            .build()
+            .unwrap();
+        CoinBuilder::default().symbol("BTC").quote("EUR", quote).build().unwrap()
+    }
+
+    #[test]
+    fn render_template() {
+        let time = Local.timestamp_opt(1_549_733_663, 0).unwrap();
+        let template: Template = "{symbol} {price:.2} {change_24h:+.1}% {{{fiat}}}".parse().unwrap();
+        assert_eq!(template.render(&coin(), "EUR", &time), "BTC 3200.98 -0.8% {EUR}");
+        let template: Template = "{price} {market_cap:.0} {change_24h:+}".parse().unwrap();
+        assert_eq!(template.render(&coin(), "EUR", &time), "3200.98 56110256905 -0.797");
+        let template: Template = "{fiat} {price}".parse().unwrap();
+        assert_eq!(template.render(&coin(), "USD", &time), "USD -");
+        let template: Template = "{time}".parse().unwrap();
+        assert_eq!(template.render(&coin(), "EUR", &time), time.to_rfc3339());
+    }
+
+    #[test]
+    fn render_missing_values() {
+        let time = Local.timestamp_opt(1_549_733_663, 0).unwrap();
+        let coin: Coin = CoinBuilder::default().symbol("XYZ").build().unwrap();
+        let template: Template = "{symbol} {price:.2}".parse().unwrap();
+        assert_eq!(template.render(&coin, "EUR", &time), "XYZ -");
+    }
+
+    #[test]
+    fn invalid_templates() {
+        assert!("{symbol".parse::<Template>().is_err());
+        assert!("symbol}".parse::<Template>().is_err());
+        assert!("{volume}".parse::<Template>().is_err());
+        assert!("{price:2}".parse::<Template>().is_err());
+        assert!("{price:.x}".parse::<Template>().is_err());
+        assert!(DEFAULT_TEMPLATE.parse::<Template>().is_ok());
+    }
+
+    #[test]
+    fn write_json_line() {
+        let time = Local.timestamp_opt(1_549_733_663, 0).unwrap();
+        let mut out = Vec::new();
+        write_coin(&mut out, &coin(), "EUR", &StreamFormat::Json, &time).unwrap();
+        let line = String::from_utf8(out).unwrap();
+        assert!(line.ends_with('\n'));
+        let result: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(result["symbol"], "BTC");
+        assert_eq!(result["fiat"], "EUR");
+        assert_eq!(result["time"], time.to_rfc3339());
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use tui::style::{Color, Modifier, Style};
+
+use super::{
+    decimal::Decimal,
+    errors::AppError,
+    types::{AppResult, PriceMove},
+};
+
+// Names of built-in themes
+pub const THEMES: [&str; 2] = ["dark", "light"];
+
+/// User defined palette, e.g. in `config.toml`
+///
+/// ```toml
+/// [themes.solarized]
+/// base = "dark"
+/// text = "#839496"
+/// selected = "yellow"
+/// ```
+///
+/// Colors not set are taken from its built-in `base` theme (`dark` by default).
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Palette {
+    pub base: Option<String>,
+    pub text: Option<String>,
+    pub selected: Option<String>,
+    pub header: Option<String>,
+    pub title: Option<String>,
+    pub positive: Option<String>,
+    pub negative: Option<String>,
+    pub flash_text: Option<String>,
+}
+
+pub type Palettes = HashMap<String, Palette>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    text: Color,
+    selected: Color,
+    header: Color,
+    title: Color,
+    positive: Color,
+    negative: Color,
+    flash_text: Color,
+    no_color: bool,
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            text: Color::Reset,
+            selected: Color::Yellow,
+            header: Color::Gray,
+            title: Color::Reset,
+            positive: Color::Green,
+            negative: Color::Red,
+            flash_text: Color::Black,
+            no_color: false,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            text: Color::Reset,
+            selected: Color::Blue,
+            header: Color::DarkGray,
+            title: Color::Reset,
+            positive: Color::Green,
+            negative: Color::Red,
+            flash_text: Color::White,
+            no_color: false,
+        }
+    }
+
+    /// Theme without any colors, which uses text modifiers only
+    pub fn no_color() -> Self {
+        Theme {
+            text: Color::Reset,
+            selected: Color::Reset,
+            header: Color::Reset,
+            title: Color::Reset,
+            positive: Color::Reset,
+            negative: Color::Reset,
+            flash_text: Color::Reset,
+            no_color: true,
+        }
+    }
+
+    /// Theme by its name, looking up built-in themes first and user defined palettes afterwards
+    pub fn by_name(name: &str, palettes: &Palettes) -> AppResult<Self> {
+        match name {
+            "dark" => Ok(Theme::dark()),
+            "light" => Ok(Theme::light()),
+            _ => {
+                let palette = palettes.get(name).ok_or_else(|| AppError::Theme { name: name.into() })?;
+                let base = palette.base.as_ref().map_or("dark", String::as_str);
+                // user defined palettes can be based on built-in themes only
+                Theme::by_name(base, &Palettes::new())?.with_palette(palette)
+            }
+        }
+    }
+
+    fn with_palette(self, palette: &Palette) -> AppResult<Self> {
+        let color = |value: &Option<String>, default: Color| match value {
+            None => Ok(default),
+            Some(v) => parse_color(v).ok_or_else(|| AppError::ThemeColor { color: v.clone() }),
+        };
+        Ok(Theme {
+            text: color(&palette.text, self.text)?,
+            selected: color(&palette.selected, self.selected)?,
+            header: color(&palette.header, self.header)?,
+            title: color(&palette.title, self.title)?,
+            positive: color(&palette.positive, self.positive)?,
+            negative: color(&palette.negative, self.negative)?,
+            flash_text: color(&palette.flash_text, self.flash_text)?,
+            no_color: self.no_color,
+        })
+    }
+
+    pub fn text(&self) -> Style {
+        Style::default().fg(self.text)
+    }
+
+    pub fn selected(&self) -> Style {
+        if self.no_color {
+            Style::default().modifier(Modifier::Invert)
+        } else {
+            Style::default().fg(self.selected)
+        }
+    }
+
+    pub fn header(&self) -> Style {
+        Style::default().fg(self.header).modifier(Modifier::Bold)
+    }
+
+    pub fn error(&self) -> Style {
+        if self.no_color {
+            Style::default().modifier(Modifier::Bold)
+        } else {
+            Style::default().fg(self.negative)
+        }
+    }
+
+    pub fn title(&self) -> Style {
+        Style::default().fg(self.title)
+    }
+
+    /// Style of a value (e.g. 24h change) based on its sign
+    pub fn change(&self, base: Style, value: &Decimal) -> Style {
+        if value.is_positive() {
+            base.fg(self.positive)
+        } else if value.is_negative() {
+            base.fg(self.negative)
+        } else {
+            base
+        }
+    }
+
+    /// Style to highlight a changed price
+    pub fn flash(&self, price_move: PriceMove) -> Option<Style> {
+        if self.no_color {
+            return match price_move {
+                PriceMove::Unchanged => None,
+                _ => Some(Style::default().modifier(Modifier::Bold)),
+            };
+        }
+        match price_move {
+            PriceMove::Up => Some(Style::default().fg(self.flash_text).bg(self.positive)),
+            PriceMove::Down => Some(Style::default().fg(self.flash_text).bg(self.negative)),
+            PriceMove::Unchanged => None,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/// Parses a color by its name (e.g. `light_red`) or as hex value (e.g. `#ff0000`)
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_lowercase();
+    if value.starts_with('#') && value.len() == 7 && value.is_ascii() {
+        let hex = |i: usize| u8::from_str_radix(&value[i..i + 2], 16).ok();
+        return match (hex(1), hex(3), hex(5)) {
+            (Some(r), Some(g), Some(b)) => Some(Color::Rgb(r, g, b)),
+            _ => None,
+        };
+    }
+    let color = match value.replace('-', "_").as_str() {
+        "reset" | "default" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark_gray" | "dark_grey" => Color::DarkGray,
+        "light_red" => Color::LightRed,
+        "light_green" => Color::LightGreen,
+        "light_yellow" => Color::LightYellow,
+        "light_blue" => Color::LightBlue,
+        "light_magenta" => Color::LightMagenta,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_colors() {
+        assert_eq!(parse_color("red"), Some(Color::Red));
+        assert_eq!(parse_color("Light-Blue"), Some(Color::LightBlue));
+        assert_eq!(parse_color("#ff8000"), Some(Color::Rgb(255, 128, 0)));
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("#gg0000"), None);
+        assert_eq!(parse_color("rainbow"), None);
+    }
+
+    #[test]
+    fn theme_by_name() {
+        let mut palettes: Palettes = HashMap::new();
+        palettes.insert(
+            "custom".into(),
+            Palette {
+                base: Some("light".into()),
+                selected: Some("#00ff00".into()),
+                ..Palette::default()
+            },
+        );
+        palettes.insert(
+            "broken".into(),
+            Palette {
+                text: Some("rainbow".into()),
+                ..Palette::default()
+            },
+        );
+
+        assert_eq!(Theme::by_name("dark", &palettes).unwrap(), Theme::dark());
+        let custom = Theme::by_name("custom", &palettes).unwrap();
+        assert_eq!(custom.selected(), Style::default().fg(Color::Rgb(0, 255, 0)));
+        assert_eq!(custom.header(), Theme::light().header());
+        assert!(Theme::by_name("broken", &palettes).is_err());
+        assert!(Theme::by_name("unknown", &palettes).is_err());
+    }
+}
@@ -1,19 +1,150 @@
+use chrono::{DateTime, Local};
+use std::collections::BTreeMap;
 use std::io::Stdout;
+use termion::input::MouseTerminal;
 use termion::raw::RawTerminal;
 use termion::screen::AlternateScreen;
 use tui::backend::TermionBackend;
-use tui::Terminal;
+use tui::{Frame, Terminal};
 
+use super::decimal::Decimal;
 use super::errors::AppError;
 
+/// Quote of a coin in a single fiat
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Builder, Default)]
+#[builder(default, setter(into))]
+pub struct Quote {
+    pub price: Option<Decimal>,
+    pub percent_change_24h: Option<Decimal>,
+    pub market_cap: Option<Decimal>,
+    /// Change of price in the last 24 hours in fiat, e.g. `-25.72`
+    #[serde(default)]
+    pub change_24h: Option<Decimal>,
+    /// Traded volume of the last 24 hours in fiat
+    #[serde(default)]
+    pub volume_24h: Option<Decimal>,
+    #[serde(default)]
+    pub open_24h: Option<Decimal>,
+    #[serde(default)]
+    pub high_24h: Option<Decimal>,
+    #[serde(default)]
+    pub low_24h: Option<Decimal>,
+    /// Time the api provider has updated the quote
+    #[serde(default)]
+    pub last_updated: Option<DateTime<Local>>,
+    /// How the price is derived, if the api provider doesn't quote it directly, e.g. `ETH/USD ÷ BTC/USD`
+    #[serde(default)]
+    pub derived_via: Option<String>,
+    /// Values as formatted by the api provider, if it sends them
+    #[serde(default)]
+    pub formatted: Option<FormattedQuote>,
+}
+
+/// Values of a quote formatted by the api provider, e.g. `€ 56.11 B`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct FormattedQuote {
+    pub price: Option<String>,
+    pub percent_change_24h: Option<String>,
+    pub market_cap: Option<String>,
+    pub volume_24h: Option<String>,
+    pub high_24h: Option<String>,
+    pub low_24h: Option<String>,
+    pub supply: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Builder, Default)]
 #[builder(default, setter(into))]
 pub struct Coin {
     #[builder(setter(into))]
     pub symbol: String,
-    pub quote: Option<f32>,
-    pub percent_change_24h: Option<f32>,
-    pub market_cap: Option<f64>,
+    /// Quotes by fiat
+    pub quotes: BTreeMap<String, Quote>,
+    /// Coins in circulation
+    #[serde(default)]
+    pub supply: Option<Decimal>,
+}
+
+#[allow(dead_code)]
+impl CoinBuilder {
+    /// Adds a quote in given fiat
+    pub fn quote<S: Into<String>>(&mut self, fiat: S, quote: Quote) -> &mut Self {
+        self.quotes.get_or_insert_with(BTreeMap::new).insert(fiat.into(), quote);
+        self
+    }
+
+    /// Adds a quote in given fiat, which has just a price
+    pub fn price<S: Into<String>, D: Into<Decimal>>(&mut self, fiat: S, price: D) -> &mut Self {
+        let quote = Quote {
+            price: Some(price.into()),
+            ..Quote::default()
+        };
+        self.quote(fiat, quote)
+    }
+}
+
+impl Coin {
+    pub fn quote(&self, fiat: &str) -> Option<&Quote> {
+        self.quotes.get(fiat)
+    }
+
+    pub fn price(&self, fiat: &str) -> Option<&Decimal> {
+        self.quote(fiat).and_then(|q| q.price.as_ref())
+    }
+
+    pub fn percent_change_24h(&self, fiat: &str) -> Option<&Decimal> {
+        self.quote(fiat).and_then(|q| q.percent_change_24h.as_ref())
+    }
+
+    /// Values of the quote in given fiat as formatted by the api provider
+    pub fn formatted(&self, fiat: &str) -> Option<&FormattedQuote> {
+        self.quote(fiat).and_then(|q| q.formatted.as_ref())
+    }
+
+    /// Direction the price in given fiat has moved compared to a previous value of the same coin
+    pub fn price_move(&self, prev: &Coin, fiat: &str) -> PriceMove {
+        match (self.price(fiat), prev.price(fiat)) {
+            (Some(q), Some(p)) if q > p => PriceMove::Up,
+            (Some(q), Some(p)) if q < p => PriceMove::Down,
+            _ => PriceMove::Unchanged,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceMove {
+    Up,
+    Down,
+    Unchanged,
+}
+
+/// Order to sort coins by. `Default` keeps the order of symbols as requested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Default,
+    Symbol,
+    Price,
+    Change,
+}
+
+impl SortOrder {
+    pub fn next(self) -> SortOrder {
+        match self {
+            SortOrder::Default => SortOrder::Symbol,
+            SortOrder::Symbol => SortOrder::Price,
+            SortOrder::Price => SortOrder::Change,
+            SortOrder::Change => SortOrder::Default,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Default => "default",
+            SortOrder::Symbol => "symbol",
+            SortOrder::Price => "price",
+            SortOrder::Change => "24h",
+        }
+    }
 }
 
 pub type CoinList = Vec<Coin>;
@@ -22,19 +153,81 @@ pub type CoinList = Vec<Coin>;
 pub struct Coins {
     pub index: usize,
     pub list: CoinList,
+    /// Api credits used to get the list, if provider reports them
+    pub credit_count: Option<u32>,
+    /// Time the list was fetched, if it's served from cache
+    pub cached_at: Option<DateTime<Local>>,
+    /// List is served from an outdated cache, because api provider failed
+    pub stale: bool,
 }
 
 impl Coins {
     pub fn new(list: CoinList) -> Self {
-        Coins { list, index: 0 }
+        Coins {
+            list,
+            index: 0,
+            credit_count: None,
+            cached_at: None,
+            stale: false,
+        }
+    }
+
+    /// Adds quotes of other coins (e.g. in another fiat) to coins of the same symbol and appends the others
+    pub fn merge(&mut self, other: Coins) {
+        for coin in other.list {
+            match self.list.iter_mut().find(|c| c.symbol == coin.symbol) {
+                Some(merged) => {
+                    merged.quotes.extend(coin.quotes);
+                    merged.supply = merged.supply.take().or(coin.supply);
+                }
+                None => self.list.push(coin),
+            }
+        }
+        self.credit_count = match (self.credit_count, other.credit_count) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
     }
 
     pub fn current(&self) -> Option<Coin> {
         self.list.get(self.index).cloned()
     }
 
-    #[allow(dead_code)]
+    pub fn select(&mut self, index: usize) -> Option<Coin> {
+        self.index = index.min(self.list.len().saturating_sub(1));
+        self.current()
+    }
+
+    pub fn select_symbol(&mut self, symbol: &str) -> Option<Coin> {
+        if let Some(index) = self.list.iter().position(|c| c.symbol == symbol) {
+            self.index = index;
+        }
+        self.current()
+    }
+
+    /// Sorts coins (prices and changes in given fiat descending) and keeps the selected coin.
+    /// `symbols` defines the order of `SortOrder::Default`.
+    pub fn sort(&mut self, order: SortOrder, symbols: &[&str], fiat: &str) {
+        let selected = self.current().map(|c| c.symbol);
+        let position = |coin: &Coin| symbols.iter().position(|s| *s == coin.symbol);
+        let desc = |a: Option<&Decimal>, b: Option<&Decimal>| b.cmp(&a);
+        match order {
+            SortOrder::Default => self.list.sort_by_key(|c| position(c)),
+            SortOrder::Symbol => self.list.sort_by(|a, b| a.symbol.cmp(&b.symbol)),
+            SortOrder::Price => self.list.sort_by(|a, b| desc(a.price(fiat), b.price(fiat))),
+            SortOrder::Change => {
+                self.list.sort_by(|a, b| desc(a.percent_change_24h(fiat), b.percent_change_24h(fiat)))
+            }
+        }
+        if let Some(symbol) = selected {
+            self.select_symbol(&symbol);
+        }
+    }
+
     pub fn prev(&mut self) -> Option<Coin> {
+        if self.list.is_empty() {
+            return None;
+        }
         self.index = if self.index >= 1 {
             self.index - 1
         } else {
@@ -43,6 +236,20 @@ impl Coins {
         self.current()
     }
 
+    /// Price moves in given fiat of all coins which have changed compared to a previous list
+    pub fn price_moves(&self, prev: &Coins, fiat: &str) -> Vec<(String, PriceMove)> {
+        self.list
+            .iter()
+            .filter_map(|coin| {
+                prev.list
+                    .iter()
+                    .find(|p| p.symbol == coin.symbol)
+                    .map(|p| (coin.symbol.clone(), coin.price_move(p, fiat)))
+            })
+            .filter(|(_, m)| *m != PriceMove::Unchanged)
+            .collect()
+    }
+
     #[allow(dead_code)]
     pub fn get_symbols(&self) -> Vec<String> {
         self.list.clone().into_iter().map(|coin| coin.symbol).collect()
@@ -51,13 +258,16 @@ impl Coins {
 
 impl Default for Coins {
     fn default() -> Coins {
-        Coins { index: 0, list: vec![] }
+        Coins::new(vec![])
     }
 }
 
 impl Iterator for Coins {
     type Item = Coin;
     fn next(&mut self) -> Option<Coin> {
+        if self.list.is_empty() {
+            return None;
+        }
         self.index = (self.index + 1) % self.list.len();
         self.current()
     }
@@ -65,8 +275,9 @@ impl Iterator for Coins {
 
 pub type AppResult<T> = Result<T, AppError>;
 
-pub type AppTerminalBackend = TermionBackend<AlternateScreen<RawTerminal<Stdout>>>;
+pub type AppTerminalBackend = TermionBackend<AlternateScreen<MouseTerminal<RawTerminal<Stdout>>>>;
 pub type AppTerminal = Terminal<AppTerminalBackend>;
+pub type AppFrame<'a> = Frame<'a, AppTerminalBackend>;
 
 #[cfg(test)]
 mod tests {
@@ -101,4 +312,106 @@ mod tests {
         coins.prev();
         assert_eq!(coins.current(), Some(coin_a.clone()))
     }
+    #[test]
+    fn coins_select() {
+        let coin_a: Coin = CoinBuilder::default().symbol("a").build().unwrap();
+        let coin_b: Coin = CoinBuilder::default().symbol("b").build().unwrap();
+        let mut coins: Coins = Coins::new(vec![coin_a.clone(), coin_b.clone()]);
+        assert_eq!(coins.select(1), Some(coin_b.clone()));
+        assert_eq!(coins.select(5), Some(coin_b.clone()));
+        assert_eq!(coins.select_symbol("a"), Some(coin_a.clone()));
+        assert_eq!(coins.select_symbol("x"), Some(coin_a.clone()));
+    }
+    #[test]
+    fn coins_merge() {
+        let mut coins = Coins::new(vec![CoinBuilder::default().symbol("a").price("EUR", 1.0).build().unwrap()]);
+        let mut other = Coins::new(vec![
+            CoinBuilder::default().symbol("a").price("USD", 2.0).build().unwrap(),
+            CoinBuilder::default().symbol("b").price("USD", 3.0).build().unwrap(),
+        ]);
+        other.credit_count = Some(1);
+        coins.merge(other);
+        assert_eq!(coins.get_symbols(), vec!["a", "b"]);
+        assert_eq!(coins.list[0].price("EUR"), Some(&Decimal::from(1.0)));
+        assert_eq!(coins.list[0].price("USD"), Some(&Decimal::from(2.0)));
+        assert_eq!(coins.credit_count, Some(1));
+    }
+
+    #[test]
+    fn coins_sort() {
+        let quote = |price: f64, change: f64| {
+            QuoteBuilder::default()
+                .price(Decimal::from(price))
+                .percent_change_24h(Decimal::from(change))
+                .build()
+                .unwrap()
+        };
+        let coin_a: Coin = CoinBuilder::default().symbol("a").quote("EUR", quote(2.0, -1.0)).build().unwrap();
+        let coin_b: Coin = CoinBuilder::default()
+            .symbol("b")
+            .quote("EUR", quote(3.0, 1.0))
+            .quote("USD", quote(1.0, -2.0))
+            .build()
+            .unwrap();
+        let coin_c: Coin = CoinBuilder::default().symbol("c").price("EUR", 1.0).build().unwrap();
+        let symbols = ["c", "a", "b"];
+        let mut coins: Coins = Coins::new(vec![coin_c.clone(), coin_a.clone(), coin_b.clone()]);
+        coins.select(1);
+        coins.sort(SortOrder::Symbol, &symbols, "EUR");
+        assert_eq!(coins.get_symbols(), vec!["a", "b", "c"]);
+        assert_eq!(coins.current(), Some(coin_a.clone()));
+        coins.sort(SortOrder::Price, &symbols, "EUR");
+        assert_eq!(coins.get_symbols(), vec!["b", "a", "c"]);
+        coins.sort(SortOrder::Change, &symbols, "EUR");
+        assert_eq!(coins.get_symbols(), vec!["b", "a", "c"]);
+        coins.sort(SortOrder::Price, &symbols, "USD");
+        assert_eq!(coins.get_symbols(), vec!["b", "a", "c"]);
+        coins.sort(SortOrder::Change, &symbols, "USD");
+        assert_eq!(coins.get_symbols(), vec!["b", "a", "c"]);
+        coins.sort(SortOrder::Default, &symbols, "EUR");
+        assert_eq!(coins.get_symbols(), vec!["c", "a", "b"]);
+        assert_eq!(coins.current(), Some(coin_a.clone()));
+    }
+    #[test]
+    fn coin_price_move() {
+        let prev: Coin = CoinBuilder::default().symbol("a").price("EUR", 2.0).build().unwrap();
+        let up: Coin = CoinBuilder::default().symbol("a").price("EUR", 2.5).build().unwrap();
+        let down: Coin = CoinBuilder::default().symbol("a").price("EUR", 1.5).build().unwrap();
+        let none: Coin = CoinBuilder::default().symbol("a").build().unwrap();
+        assert_eq!(up.price_move(&prev, "EUR"), PriceMove::Up);
+        assert_eq!(down.price_move(&prev, "EUR"), PriceMove::Down);
+        assert_eq!(prev.price_move(&prev, "EUR"), PriceMove::Unchanged);
+        assert_eq!(none.price_move(&prev, "EUR"), PriceMove::Unchanged);
+        assert_eq!(up.price_move(&prev, "USD"), PriceMove::Unchanged);
+    }
+    #[test]
+    fn coin_price_move_exact() {
+        let coin = |fiat: &str, price: &str| {
+            let price: Decimal = price.parse().unwrap();
+            CoinBuilder::default().symbol("a").price(fiat, price).build().unwrap()
+        };
+        // both prices are the same as `f32`
+        let (up, prev) = (coin("IRR", "16777217"), coin("IRR", "16777216"));
+        assert_eq!(up.price_move(&prev, "IRR"), PriceMove::Up);
+        let (down, prev) = (coin("USD", "0.000008120000001"), coin("USD", "0.000008120000002"));
+        assert_eq!(down.price_move(&prev, "USD"), PriceMove::Down);
+    }
+    #[test]
+    fn coins_price_moves() {
+        let prev: Coins = Coins::new(vec![
+            CoinBuilder::default().symbol("a").price("EUR", 1.0).build().unwrap(),
+            CoinBuilder::default().symbol("b").price("EUR", 1.0).build().unwrap(),
+            CoinBuilder::default().symbol("c").price("EUR", 1.0).build().unwrap(),
+        ]);
+        let coins: Coins = Coins::new(vec![
+            CoinBuilder::default().symbol("a").price("EUR", 2.0).build().unwrap(),
+            CoinBuilder::default().symbol("b").price("EUR", 1.0).build().unwrap(),
+            CoinBuilder::default().symbol("c").price("EUR", 0.5).build().unwrap(),
+            CoinBuilder::default().symbol("d").price("EUR", 0.5).build().unwrap(),
+        ]);
+        assert_eq!(
+            coins.price_moves(&prev, "EUR"),
+            vec![("a".to_string(), PriceMove::Up), ("c".to_string(), PriceMove::Down)]
+        );
+    }
 }
@@ -0,0 +1,159 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::{Block, Widget};
+
+/// Content of a single cell of a `StyledTable`
+pub struct TableCell {
+    pub content: String,
+    pub style: Style,
+}
+
+impl TableCell {
+    pub fn new<S: Into<String>>(content: S, style: Style) -> Self {
+        TableCell {
+            content: content.into(),
+            style,
+        }
+    }
+}
+
+/// Table similar to `tui::widgets::Table`, but with a style per cell instead of a style per row
+pub struct StyledTable<'a> {
+    block: Option<Block<'a>>,
+    header: Vec<TableCell>,
+    widths: &'a [u16],
+    column_spacing: u16,
+    rows: Vec<Vec<TableCell>>,
+}
+
+impl<'a> StyledTable<'a> {
+    pub fn new(header: Vec<TableCell>, rows: Vec<Vec<TableCell>>) -> Self {
+        StyledTable {
+            block: None,
+            header,
+            widths: &[],
+            column_spacing: 1,
+            rows,
+        }
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn widths(mut self, widths: &'a [u16]) -> Self {
+        self.widths = widths;
+        self
+    }
+
+    pub fn column_spacing(mut self, spacing: u16) -> Self {
+        self.column_spacing = spacing;
+        self
+    }
+
+    fn draw_row(&self, cells: &[TableCell], x: u16, y: u16, max_width: u16, buf: &mut Buffer) {
+        let mut x = x;
+        for (w, cell) in self.widths.iter().zip(cells) {
+            if x + w > max_width {
+                break;
+            }
+            // pad content to fill the whole cell with its background
+            buf.set_stringn(x, y, format!("{:1$}", cell.content, *w as usize), *w as usize, cell.style);
+            x += w + self.column_spacing;
+        }
+    }
+}
+
+/// Index of the column of a table at given x position (relative to the left of the table)
+pub fn column_at(widths: &[u16], column_spacing: u16, x: u16) -> Option<usize> {
+    let mut left = 0;
+    for (i, w) in widths.iter().enumerate() {
+        if x >= left && x < left + w {
+            return Some(i);
+        }
+        left += w + column_spacing;
+    }
+    None
+}
+
+/// Scales values to bars of a sparkline with given width. The latest value of each bar is used,
+/// the lowest value is shown as a bar of height 1 and the highest as a bar of height 100.
+pub fn sparkline_data(values: &[f64], width: usize) -> Vec<u64> {
+    if values.is_empty() || width == 0 {
+        return vec![];
+    }
+    let bars = values.len().min(width);
+    let sampled: Vec<f64> = (1..=bars).map(|i| values[i * values.len() / bars - 1]).collect();
+    let min = sampled.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = sampled.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    sampled
+        .iter()
+        .map(|v| if max > min { 1 + ((v - min) / (max - min) * 99.0).round() as u64 } else { 50 })
+        .collect()
+}
+
+impl<'a> Widget for StyledTable<'a> {
+    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        let table_area = match self.block {
+            Some(ref mut b) => {
+                b.draw(area, buf);
+                b.inner(area)
+            }
+            None => area,
+        };
+
+        let mut y = table_area.top();
+        if y < table_area.bottom() {
+            self.draw_row(&self.header, table_area.left(), y, table_area.right(), buf);
+        }
+        y += 2;
+
+        for row in &self.rows {
+            if y >= table_area.bottom() {
+                break;
+            }
+            self.draw_row(row, table_area.left(), y, table_area.right(), buf);
+            y += 1;
+        }
+    }
+}
+
+/// Resets all cells of an area, e.g. to draw an overlay on top of other widgets
+pub struct Clear;
+
+impl Widget for Clear {
+    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                buf.get_mut(x, y).reset();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn column_at_position() {
+        let widths = [10, 16, 10];
+        assert_eq!(column_at(&widths, 1, 0), Some(0));
+        assert_eq!(column_at(&widths, 1, 9), Some(0));
+        assert_eq!(column_at(&widths, 1, 10), None);
+        assert_eq!(column_at(&widths, 1, 11), Some(1));
+        assert_eq!(column_at(&widths, 1, 28), Some(2));
+        assert_eq!(column_at(&widths, 1, 40), None);
+    }
+
+    #[test]
+    fn scale_sparkline_data() {
+        assert_eq!(sparkline_data(&[], 10), Vec::<u64>::new());
+        assert_eq!(sparkline_data(&[10.0, 20.0, 15.0], 10), vec![1, 100, 51]);
+        assert_eq!(sparkline_data(&[10.0, 11.0, 20.0, 21.0], 2), vec![1, 100]);
+        assert_eq!(sparkline_data(&[5.0, 5.0], 2), vec![50, 50]);
+    }
+}
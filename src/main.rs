@@ -19,19 +19,80 @@ mod app;
 
 use app ::{
     api::{
-        cryptocompare::{CryptoCompare},
-        coinmarketcap::{CoinMarketCap},
-        {Api},
+        cache::CachedApi,
+        cross::CrossRateApi,
+        fixture::{RecordingApi, ReplayApi, DEFAULT_FIXTURES_DIR},
+        registry::{default_provider, provider_by_name, provider_names, providers_help, PROVIDERS},
+        {Api, Capabilities},
     },
-    config::Config
+    config::{Config, ConfigFile},
+    convert::{fetch_conversion, ConversionQuery},
+    decimal::Decimal,
+    env::get_env,
+    export::{export_history, import_history, parse_time, ExportFormat, EXPORT_FORMATS},
+    format::{DisplayMode, Locale, DISPLAY_MODES, LOCALES},
+    history::{History, HistoryFilter, Retention},
+    keys::{KeyMap, KEYMAPS},
+    metrics::{serve_metrics, DEFAULT_METRICS_ADDR},
+    mock_server::{serve_mock, Failure, MockOptions, MockServer, DEFAULT_MOCK_ADDR, FAILURES},
+    output::{print_coins, write_providers, OutputFormat, OUTPUT_FORMATS},
+    rest::{serve_coins, DEFAULT_SERVE_ADDR},
+    signal::{is_stdout_closed, stop_on_interrupt},
+    stream::{stream_coins, StreamFormat, Template, DEFAULT_TEMPLATE},
+    theme::{Theme, THEMES},
 };
 
-use clap::{App, Arg};
+use clap::{App, Arg, SubCommand};
+use std::fmt::Display;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
 
-fn main() {
-    env_logger::init();
+fn exit_with_error<E: Display>(e: E) -> ! {
+    eprintln!("Ooops, something went wrong: {}", e);
+    process::exit(1)
+}
 
-    let matches = App::new(crate_name!())
+/// Help texts and values of args, which are built at runtime
+struct CliHelp {
+    theme: String,
+    keymap: String,
+    locale: String,
+    provider: String,
+    provider_names: Vec<&'static str>,
+}
+
+impl CliHelp {
+    fn new() -> Self {
+        CliHelp {
+            theme: format!(
+                "Theme to use, which is one of the built-in themes ({}) or a theme defined in config file [default: {}]",
+                THEMES.join(", "),
+                THEMES[0]
+            ),
+            keymap: format!(
+                "Key bindings to use. Single bindings can be changed in config file [default: {}]",
+                KEYMAPS[0]
+            ),
+            locale: format!(
+                "Locale to format numbers and prices, which is one of {} [default: from LANG or {}]",
+                LOCALES.join(", "),
+                LOCALES[0]
+            ),
+            provider: format!(
+                "Api provider to get data of cryptocurrencies from, which is one of: {} [default: {}]",
+                providers_help(),
+                default_provider().name
+            ),
+            provider_names: provider_names(),
+        }
+    }
+}
+
+fn cli(help: &CliHelp) -> App<'_, '_> {
+    App::new(crate_name!())
         .about(crate_description!())
         .author(crate_authors!())
         .version(crate_version!())
@@ -42,47 +103,517 @@ fn main() {
                 .long("cryptos")
                 .use_delimiter(true)
                 .multiple(true)
-                .default_value("BTC"),
+                .number_of_values(1)
+                .default_value("BTC")
+                .global(true),
             Arg::with_name("fiat")
-                .help("Fiat currency to compare with, e.g. EUR")
+                .help("Currency to compare with, a fiat currency (e.g. EUR) or a cryptocurrency (e.g. BTC). Pairs not quoted by the api provider are derived via USD. Multiple values are possible to add using ',' as a delimiter, e.g. EUR,USD,CHF. The first one is used to sort and to show changes")
                 .short("f")
                 .long("fiat")
+                .use_delimiter(true)
+                .multiple(true)
+                .number_of_values(1)
                 .default_value("USD")
-                .possible_values(&app::constants::FIAT_LIST),
+                .validator(|v| {
+                    let symbol = v.as_str();
+                    if app::constants::FIAT_LIST.contains(&symbol) || app::constants::CRYPTO_LIST.contains(&symbol) {
+                        Ok(())
+                    } else {
+                        Err(format!("Currency {} is neither a known fiat (e.g. EUR) nor cryptocurrency (e.g. BTC)", v))
+                    }
+                })
+                .global(true),
             Arg::with_name("api provider")
-                .help("Api provider, which supports public endpoints to get data of cryptocurrencies. Currently supported is just CoinMarketCap, but more will be added soon...")
+                .help(&help.provider)
                 .short("p")
                 .long("provider")
-                .default_value(&app::constants::API_PROVIDERS[0])
-                .possible_values(&app::constants::API_PROVIDERS),
+                .takes_value(true)
+                .possible_values(&help.provider_names)
+                .global(true),
+            Arg::with_name("interval")
+                .help("Interval in seconds to refresh data of cryptocurrencies")
+                .short("i")
+                .long("interval")
+                .default_value(app::constants::DEFAULT_REFRESH_INTERVAL)
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|_| String::from("Interval has to be a number")))
+                .global(true),
+            Arg::with_name("cache-ttl")
+                .help("Seconds to cache responses of api provider on disk, e.g. to save credits while doing some development. Cached responses are also used if api provider fails.")
+                .long("cache-ttl")
+                .takes_value(true)
+                .validator(|v| v.parse::<u64>().map(|_| ()).map_err(|_| String::from("TTL has to be a number")))
+                .global(true),
+            Arg::with_name("theme")
+                .help(&help.theme)
+                .short("t")
+                .long("theme")
+                .takes_value(true)
+                .global(true),
+            Arg::with_name("no-color")
+                .help("Disable colors. Setting the `NO_COLOR` environment variable does the same.")
+                .long("no-color")
+                .global(true),
+            Arg::with_name("keymap")
+                .help(&help.keymap)
+                .short("k")
+                .long("keymap")
+                .takes_value(true)
+                .possible_values(&KEYMAPS)
+                .global(true),
+            Arg::with_name("locale")
+                .help(&help.locale)
+                .long("locale")
+                .takes_value(true)
+                .global(true),
+            Arg::with_name("display")
+                .help("Show values as formatted by the api provider (if it sends them, e.g. cryptocompare), or format all values locally [default: local]")
+                .long("display")
+                .takes_value(true)
+                .possible_values(&DISPLAY_MODES)
+                .global(true),
+            Arg::with_name("config")
+                .help("Path to a config file [default: ~/.config/wtch-crpts/config.toml]")
+                .long("config")
+                .takes_value(true)
+                .global(true),
+            Arg::with_name("history")
+                .help("Path to a SQLite database to store all fetched data in, which enables history (also by `enabled = true` in section [history] of config file) [default: ~/.local/share/wtch-crpts/history.db]. It's never stored in development mode or using --endpoint")
+                .long("history")
+                .takes_value(true)
+                .global(true),
+            Arg::with_name("no-history")
+                .help("Disable storing fetched data in history, even if it's enabled in config file")
+                .long("no-history")
+                .conflicts_with("history")
+                .global(true),
+            Arg::with_name("fixtures")
+                .help("Directory of recorded responses to replay in development mode")
+                .long("fixtures")
+                .default_value(DEFAULT_FIXTURES_DIR)
+                .hidden_short_help(true)
+                .global(true),
+            Arg::with_name("endpoint")
+                .help("Endpoint of api provider to use instead of its public endpoint, e.g. http://localhost:3000 of mock-server")
+                .long("endpoint")
+                .takes_value(true)
+                .conflicts_with("dev")
+                .global(true),
+            Arg::with_name("record")
+                .help("Directory to record responses of api provider to (with api keys scrubbed), e.g. to replay them in development mode")
+                .long("record")
+                .takes_value(true)
+                .conflicts_with("dev")
+                .global(true),
             Arg::with_name("dev")
-                .help("Flag to run app in development mode. This might be helpful to serve mock data.")
+                .help("Flag to run app in development mode to replay recorded responses of api provider (see --fixtures) instead of sending any requests.")
                 .short("d")
                 .long("development")
                 .default_value("false")
                 .hidden_short_help(true)
-                .hidden_long_help(true),
+                .hidden_long_help(true)
+                .global(true),
         ])
-        .get_matches();
+        .subcommand(
+            SubCommand::with_name("print")
+                .about("Prints data of cryptocurrencies once to stdout, e.g. to use it in scripts")
+                .arg(
+                    Arg::with_name("format")
+                        .help("Output format")
+                        .long("format")
+                        .default_value(OUTPUT_FORMATS[0])
+                        .possible_values(&OUTPUT_FORMATS),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("Converts an amount between two currencies using current rates, e.g. convert 0.5 BTC EUR. Cryptocurrencies are converted through the first fiat, if there is no direct rate")
+                .args(&[
+                    Arg::with_name("amount")
+                        .help("Amount to convert, e.g. 0.5")
+                        .required(true),
+                    Arg::with_name("from")
+                        .help("Currency to convert from, a cryptocurrency (e.g. BTC) or a fiat currency (e.g. EUR)")
+                        .required(true),
+                    Arg::with_name("to")
+                        .help("Currency to convert to, a cryptocurrency (e.g. ETH) or a fiat currency (e.g. EUR)")
+                        .required(true),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("stream")
+                .about("Prints one line per cryptocurrency to stdout whenever data are refreshed, e.g. for status bars or logs")
+                .args(&[
+                    Arg::with_name("template")
+                        .help("Template of a line. Fields are {time}, {symbol}, {fiat}, {price}, {change_24h} and {market_cap}. Numbers can be formatted, e.g. {price:.2} or {change_24h:+.1}")
+                        .long("template")
+                        .default_value(DEFAULT_TEMPLATE),
+                    Arg::with_name("json")
+                        .help("Print one JSON object per line instead of using a template")
+                        .long("json")
+                        .conflicts_with("template"),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Serves data of cryptocurrencies as JSON at /coins, /coins/{symbol} and /health")
+                .arg(
+                    Arg::with_name("listen")
+                        .help("Address to listen on")
+                        .long("listen")
+                        .default_value(DEFAULT_SERVE_ADDR),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("serve-metrics")
+                .about("Serves data of cryptocurrencies as Prometheus metrics at /metrics")
+                .arg(
+                    Arg::with_name("listen")
+                        .help("Address to listen on")
+                        .long("listen")
+                        .default_value(DEFAULT_METRICS_ADDR),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Exports history to stdout, e.g. to analyse it in spreadsheets")
+                .args(&[
+                    Arg::with_name("format")
+                        .help("Output format")
+                        .long("format")
+                        .possible_values(&EXPORT_FORMATS)
+                        .default_value(EXPORT_FORMATS[0]),
+                    Arg::with_name("from")
+                        .help("Export data since this date or time, e.g. 2019-02-01 or 2019-02-01T12:00:00+01:00")
+                        .long("from")
+                        .takes_value(true),
+                    Arg::with_name("to")
+                        .help("Export data until this date (including the whole day) or time")
+                        .long("to")
+                        .takes_value(true),
+                    Arg::with_name("symbols")
+                        .help("Export data of these cryptocurrencies only, e.g. BTC,ETH")
+                        .long("symbols")
+                        .use_delimiter(true)
+                        .multiple(true)
+                        .number_of_values(1),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Imports data exported before into history")
+                .args(&[
+                    Arg::with_name("file")
+                        .help("File to import, or '-' to read stdin")
+                        .required(true),
+                    Arg::with_name("format")
+                        .help("Input format")
+                        .long("format")
+                        .possible_values(&EXPORT_FORMATS)
+                        .default_value(EXPORT_FORMATS[0]),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("providers")
+                .about("Prints supported api providers and their capabilities, e.g. which fiats they quote"),
+        )
+        .subcommand(
+            SubCommand::with_name("mock-server")
+                .about("Serves recorded responses (see --fixtures) on the paths of api providers, e.g. to use them with --endpoint")
+                .args(&[
+                    Arg::with_name("listen")
+                        .help("Address to listen on")
+                        .long("listen")
+                        .default_value(DEFAULT_MOCK_ADDR),
+                    Arg::with_name("latency")
+                        .help("Delay of every response in milliseconds")
+                        .long("latency")
+                        .default_value("0"),
+                    Arg::with_name("jitter")
+                        .help("Maximum random change of prices in percent")
+                        .long("jitter")
+                        .default_value("0"),
+                    Arg::with_name("fail")
+                        .help("Failure to respond with instead of recorded responses")
+                        .long("fail")
+                        .takes_value(true)
+                        .possible_values(&FAILURES),
+                    Arg::with_name("fail-rate")
+                        .help("Probability of failures in percent")
+                        .long("fail-rate")
+                        .default_value("100"),
+                ]),
+        )
+}
 
-    let fiat = matches.value_of("fiat").expect("fiat has to be set");
+fn main() {
+    env_logger::init();
+
+    let help = CliHelp::new();
+    let matches = cli(&help).get_matches();
+    // args are global to be used by subcommands as well
+    let (subcommand, sub_matches) = matches.subcommand();
+    let matches = sub_matches.unwrap_or(&matches);
+
+    let fiats: Vec<&str> = matches.values_of("fiat").expect("One or more fiat has to be set").collect();
     let is_development = matches.occurrences_of("dev") == 1;
     let cryptos: Vec<&str> = matches
         .values_of("cryptocurrencies")
         .expect("One or more cryptocurrency has to be set")
         .collect();
 
-    let api_value = matches.value_of("api provider").expect("A API provider has to be defined");
-    let api = match api_value {
-        "coinmarketcap" => Box::new(CoinMarketCap::new(is_development)) as Box<Api>,
-        "cryptocompare" => Box::new(CryptoCompare::new(is_development)) as Box<Api>,
-        _ => panic!("Provider {} is not supported ", api_value), // `clap` already catch this, so it will never happen
+    let interval = value_t!(matches, "interval", u64).unwrap_or_else(|e| e.exit());
+
+    let mut config_file = ConfigFile::load(matches.value_of("config")).unwrap_or_else(|e| exit_with_error(e));
+    config_file.http = config_file.http.clone().within_interval(Duration::from_secs(interval));
+    let no_color = matches.is_present("no-color") || get_env("NO_COLOR").map(|v| !v.is_empty()).unwrap_or(false);
+    let theme = if no_color {
+        Theme::no_color()
+    } else {
+        let theme_name = matches
+            .value_of("theme")
+            .or(config_file.theme.as_deref())
+            .unwrap_or(THEMES[0]);
+        Theme::by_name(theme_name, &config_file.themes).unwrap_or_else(|e| exit_with_error(e))
     };
 
-    let mut app = app::App::new(Config::new(cryptos, fiat, is_development, api));
+    let custom_history_path = matches.value_of("history").or(config_file.history.path.as_deref());
+    let history_path = custom_history_path.map(PathBuf::from).or_else(History::default_path);
+    let open_history = || match &history_path {
+        Some(path) => History::open(path, Retention::from(&config_file.history)).unwrap_or_else(|e| exit_with_error(e)),
+        None => exit_with_error("No directory to store history found, please use --history"),
+    };
+
+    if subcommand == "export" {
+        let history = open_history();
+        let time = |name, end_of_day| matches.value_of(name).map(|v| parse_time(v, end_of_day).unwrap_or_else(|e| exit_with_error(e)));
+        let filter = HistoryFilter {
+            from: time("from", false),
+            to: time("to", true),
+            symbols: matches.values_of("symbols").map(|v| v.map(String::from).collect()).unwrap_or_default(),
+        };
+        let format = value_t!(matches, "format", ExportFormat).unwrap_or_else(|e| e.exit());
+        let stdout = io::stdout();
+        match export_history(&history, &filter, format, stdout.lock()) {
+            Ok(count) => info!("exported {} quotes", count),
+            Err(e) => exit_with_error(e),
+        }
+        process::exit(0)
+    }
+
+    if subcommand == "import" {
+        let history = open_history();
+        let format = value_t!(matches, "format", ExportFormat).unwrap_or_else(|e| e.exit());
+        let file = matches.value_of("file").expect("file has to be set");
+        let result = if file == "-" {
+            import_history(&history, format, io::stdin())
+        } else {
+            let f = File::open(file).unwrap_or_else(|e| exit_with_error(format!("Failed to read {}: {}", file, e)));
+            import_history(&history, format, f)
+        };
+        match result {
+            Ok(count) => println!("Imported {} quotes", count),
+            Err(e) => exit_with_error(e),
+        }
+        process::exit(0)
+    }
+
+    if subcommand == "providers" {
+        // providers, which aren't configured (e.g. custom), are left out
+        let providers: Vec<(&str, Capabilities)> = PROVIDERS
+            .iter()
+            .filter_map(|p| p.create(None, &config_file).ok().map(|api| (p.name, api.capabilities())))
+            .collect();
+        if let Err(e) = write_providers(&providers, io::stdout()) {
+            exit_with_error(e)
+        }
+        process::exit(0)
+    }
+
+    if subcommand == "mock-server" {
+        let options = MockOptions {
+            latency: Duration::from_millis(value_t!(matches, "latency", u64).unwrap_or_else(|e| e.exit())),
+            jitter: value_t!(matches, "jitter", f64).unwrap_or_else(|e| e.exit()),
+            failure: matches
+                .value_of("fail")
+                .map(|_| value_t!(matches, "fail", Failure).unwrap_or_else(|e| e.exit())),
+            failure_rate: value_t!(matches, "fail-rate", f64).unwrap_or_else(|e| e.exit()),
+        };
+        let fixtures = matches.value_of("fixtures").unwrap_or(DEFAULT_FIXTURES_DIR);
+        let mock = MockServer::new(PathBuf::from(fixtures), options);
+        let addr = matches.value_of("listen").unwrap_or(DEFAULT_MOCK_ADDR);
+        let running = stop_on_interrupt().unwrap_or_else(|e| exit_with_error(e));
+        if let Err(e) = serve_mock(mock, addr, running) {
+            exit_with_error(e)
+        }
+        process::exit(0)
+    }
+
+    // history is stored only if it's asked for, and never of replayed or mocked responses
+    let history_wanted = matches.is_present("history")
+        || config_file.history.enabled.unwrap_or_else(|| config_file.history.path.is_some());
+    let is_fake = is_development || matches.is_present("endpoint");
+    if history_wanted && is_fake {
+        info!("history is disabled while using fixtures or another endpoint");
+    }
+    let history_enabled = history_wanted && !is_fake && !matches.is_present("no-history");
+    let history = match &history_path {
+        Some(path) if history_enabled => match History::open(path, Retention::from(&config_file.history)) {
+            Ok(history) => Some(history),
+            // history at the default path is optional, so it's just logged if it can't be opened
+            Err(e) if custom_history_path.is_none() => {
+                warn!("failed to open history {}", e);
+                None
+            }
+            Err(e) => exit_with_error(e),
+        },
+        _ => None,
+    };
+
+    let keymap_name = matches
+        .value_of("keymap")
+        .or(config_file.keymap.as_deref())
+        .unwrap_or(KEYMAPS[0]);
+    let keymap = KeyMap::by_name(keymap_name, &config_file.keys).unwrap_or_else(|e| exit_with_error(e));
+
+    let locale = match matches.value_of("locale").or(config_file.locale.as_deref()) {
+        Some(name) => Locale::by_name(name).unwrap_or_else(|e| exit_with_error(e)),
+        None => Locale::from_env().unwrap_or_default(),
+    };
+    let display_mode = matches
+        .value_of("display")
+        .or(config_file.display.as_deref())
+        .map(|name| name.parse::<DisplayMode>().unwrap_or_else(|e| exit_with_error(e)))
+        .unwrap_or_default();
+
+    let api_value = matches
+        .value_of("api provider")
+        .or(config_file.provider.as_deref())
+        .unwrap_or(default_provider().name);
+    let provider = provider_by_name(api_value)
+        .unwrap_or_else(|e| exit_with_error(e))
+        .create(matches.value_of("endpoint"), &config_file)
+        .unwrap_or_else(|e| exit_with_error(e));
+    let api = if is_development {
+        let fixtures = matches.value_of("fixtures").unwrap_or(DEFAULT_FIXTURES_DIR);
+        Box::new(ReplayApi::new(provider, PathBuf::from(fixtures))) as Box<dyn Api>
+    } else if let Some(dir) = matches.value_of("record") {
+        Box::new(RecordingApi::new(provider, PathBuf::from(dir))) as Box<dyn Api>
+    } else {
+        provider as Box<dyn Api>
+    };
+    let capabilities = api.capabilities();
+    capabilities.check_fiats(api_value, &fiats).unwrap_or_else(|e| exit_with_error(e));
+    capabilities.check_key(api_value).unwrap_or_else(|e| exit_with_error(e));
+    let api = Box::new(CrossRateApi::new(api)) as Box<dyn Api>;
+    let cache_ttl = if matches.is_present("cache-ttl") {
+        Some(value_t!(matches, "cache-ttl", u64).unwrap_or_else(|e| e.exit()))
+    } else {
+        config_file.cache.ttl
+    };
+    let cache_dir = config_file.cache.dir.as_ref().map(PathBuf::from).or_else(CachedApi::default_dir);
+    let api = match (cache_ttl, cache_dir) {
+        (Some(ttl), Some(dir)) => Box::new(CachedApi::new(api, dir, Duration::from_secs(ttl))) as Box<dyn Api>,
+        _ => api,
+    };
+
+    let mut config = Config::new(cryptos, fiats, api, Duration::from_secs(interval), theme, keymap)
+        .with_locale(locale)
+        .with_display_mode(display_mode);
+    if let Some(history) = history {
+        config = config.with_history(history);
+    }
+
+    if subcommand == "print" {
+        let format = value_t!(matches, "format", OutputFormat).unwrap_or_else(|e| e.exit());
+        match print_coins(&config, format, io::stdout()) {
+            Ok(ref unresolved) if unresolved.is_empty() => process::exit(0),
+            Ok(unresolved) => {
+                eprintln!("Unresolved symbols: {}", unresolved.join(", "));
+                process::exit(2)
+            }
+            Err(e) => exit_with_error(e),
+        }
+    }
+
+    if subcommand == "convert" {
+        let query = ConversionQuery {
+            amount: value_t!(matches, "amount", Decimal).unwrap_or_else(|e| e.exit()),
+            from: matches.value_of("from").unwrap_or_default().to_uppercase(),
+            to: matches.value_of("to").unwrap_or_default().to_uppercase(),
+        };
+        match fetch_conversion(config.api.as_ref(), &query, config.primary_fiat()) {
+            Ok(conversion) => println!("{}", conversion),
+            Err(e) => exit_with_error(e),
+        }
+        process::exit(0)
+    }
+
+    if subcommand == "stream" {
+        let format = if matches.is_present("json") {
+            StreamFormat::Json
+        } else {
+            StreamFormat::Line(value_t!(matches, "template", Template).unwrap_or_else(|e| e.exit()))
+        };
+        let running = stop_on_interrupt().unwrap_or_else(|e| exit_with_error(e));
+        let stdout = io::stdout();
+        if let Err(e) = stream_coins(&config, &format, stdout.lock(), running, is_stdout_closed) {
+            exit_with_error(e)
+        }
+        process::exit(0)
+    }
+
+    if subcommand == "serve" {
+        let addr = matches.value_of("listen").unwrap_or(DEFAULT_SERVE_ADDR);
+        let running = stop_on_interrupt().unwrap_or_else(|e| exit_with_error(e));
+        if let Err(e) = serve_coins(&config, addr, running) {
+            exit_with_error(e)
+        }
+        process::exit(0)
+    }
+
+    if subcommand == "serve-metrics" {
+        let addr = matches.value_of("listen").unwrap_or(DEFAULT_METRICS_ADDR);
+        let running = stop_on_interrupt().unwrap_or_else(|e| exit_with_error(e));
+        if let Err(e) = serve_metrics(&config, addr, running) {
+            exit_with_error(e)
+        }
+        process::exit(0)
+    }
+
+    let mut app = app::App::new(config);
     let msg = match app.run() {
         Ok(_) => String::from("Goodbye!"),
         Err(e) => format!("Ooops, something went wrong: {}", e),
     };
     println!("{}", msg);
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_keymap() {
+        let help = CliHelp::new();
+        let matches = cli(&help).get_matches_from_safe(["wtch-crpts", "-k", "vim", "print"]).unwrap();
+        assert_eq!(matches.value_of("keymap"), Some("vim"));
+        assert_eq!(matches.subcommand_name(), Some("print"));
+    }
+
+    #[test]
+    fn parse_display() {
+        let help = CliHelp::new();
+        let matches = cli(&help).get_matches_from_safe(["wtch-crpts", "--display", "provider", "print"]).unwrap();
+        assert_eq!(matches.value_of("display"), Some("provider"));
+        assert_eq!(matches.subcommand_name(), Some("print"));
+    }
+
+    #[test]
+    fn parse_provider() {
+        let help = CliHelp::new();
+        let matches = cli(&help).get_matches_from_safe(["wtch-crpts", "-p", "cryptocompare", "print"]).unwrap();
+        assert_eq!(matches.value_of("api provider"), Some("cryptocompare"));
+        assert_eq!(matches.subcommand_name(), Some("print"));
+    }
+}